@@ -50,7 +50,15 @@ print_status "Installing binary to /usr/local/bin..."
 sudo cp target/release/ocypus-l24-digital /usr/local/bin/
 sudo chmod 755 /usr/local/bin/ocypus-l24-digital
 
-# Note: Configuration is now done via CLI arguments in the systemd service file
+# Install default configuration (keep an existing one)
+if [ ! -f /etc/ocypus-digital/config.toml ]; then
+    print_status "Creating default configuration at /etc/ocypus-digital/config.toml..."
+    sudo mkdir -p /etc/ocypus-digital
+    sudo cp config.example.toml /etc/ocypus-digital/config.toml
+    sudo chmod 644 /etc/ocypus-digital/config.toml
+else
+    print_warning "Keeping existing configuration at /etc/ocypus-digital/config.toml"
+fi
 
 # Install systemd service
 print_status "Installing systemd service..."
@@ -75,15 +83,15 @@ sudo udevadm trigger
 print_status "Installation completed successfully!"
 print_status ""
 print_status "Next steps:"
-print_status "1. Edit service configuration: sudo nano /etc/systemd/system/ocypus-digital.service"
+print_status "1. Edit configuration: sudo nano /etc/ocypus-digital/config.toml"
 print_status "2. Start the service: sudo systemctl start ocypus-digital.service"
 print_status "3. Check status: sudo systemctl status ocypus-digital.service"
 print_status "4. View logs: sudo journalctl -u ocypus-digital.service -f"
 print_status ""
 print_status "The service will automatically start on boot."
 print_status ""
-print_status "To modify service settings, edit the ExecStart line in the service file"
-print_status "and then run: sudo systemctl daemon-reload && sudo systemctl restart ocypus-digital.service"
+print_status "To modify settings, edit /etc/ocypus-digital/config.toml"
+print_status "and then run: sudo systemctl restart ocypus-digital.service"
 
 # Ask if user wants to start the service now
 read -p "Do you want to start the service now? (y/N): " -n 1 -r
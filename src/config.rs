@@ -1,5 +1,11 @@
 use crate::error::{OcypusError, Result};
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
 /// Device constants
@@ -8,6 +14,9 @@ pub const PID: u16 = 0x434d;
 pub const REPORT_ID: u8 = 0x07;
 pub const REPORT_LENGTH: usize = 64;
 
+/// System-wide configuration file
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/ocypus-digital/config.toml";
+
 /// Temperature unit
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TemperatureUnit {
@@ -33,34 +42,276 @@ impl TemperatureUnit {
             TemperatureUnit::Fahrenheit => 'F',
         }
     }
+
+    /// Convert a temperature in °C to this unit
+    pub fn from_celsius(self, celsius: f32) -> f32 {
+        match self {
+            TemperatureUnit::Celsius => celsius,
+            TemperatureUnit::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+        }
+    }
 }
 
-/// Sensor type
+/// Sensor selection: a registered source name plus an optional selector,
+/// written as `name` or `name:selector` (e.g. `thermal:x86_pkg_temp`)
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum SensorType {
-    Cpu,
-    Gpu,
+pub struct SensorType {
+    name: String,
+    selector: Option<String>,
 }
 
 impl SensorType {
-    pub fn from_str(s: &str) -> Result<Self> {
-        match s.to_lowercase().as_str() {
-            "cpu" => Ok(SensorType::Cpu),
-            "gpu" => Ok(SensorType::Gpu),
-            _ => Err(OcypusError::InvalidSensorType(s.to_string())),
+    /// Create a sensor selection without a selector
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_lowercase(),
+            selector: None,
+        }
+    }
+
+    /// Name of the source to use
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Backend-specific selector, if any
+    pub fn selector(&self) -> Option<&str> {
+        self.selector.as_deref()
+    }
+}
+
+impl FromStr for SensorType {
+    type Err = OcypusError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, selector) = match s.split_once(':') {
+            Some((name, selector)) => (name.trim(), Some(selector.trim())),
+            None => (s.trim(), None),
+        };
+
+        if name.is_empty() || selector.is_some_and(str::is_empty) {
+            return Err(OcypusError::InvalidSensorType(
+                s.to_string(),
+                "expected 'name' or 'name:selector'".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            name: name.to_lowercase(),
+            selector: selector.map(str::to_string),
+        })
+    }
+}
+
+impl fmt::Display for SensorType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.selector {
+            Some(selector) => write!(f, "{}:{}", self.name, selector),
+            None => f.write_str(&self.name),
+        }
+    }
+}
+
+/// Selects one display among several matching devices
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceSelector {
+    /// USB serial number
+    Serial(String),
+    /// Platform device path (e.g. `/dev/hidraw3`)
+    Path(String),
+}
+
+impl FromStr for DeviceSelector {
+    type Err = OcypusError;
+
+    /// Parse `serial:<serial>` or `path:<path>`; a bare value is a path if it
+    /// starts with `/`, otherwise a serial number
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let selector = if let Some(serial) = s.strip_prefix("serial:") {
+            DeviceSelector::Serial(serial.to_string())
+        } else if let Some(path) = s.strip_prefix("path:") {
+            DeviceSelector::Path(path.to_string())
+        } else if s.starts_with('/') {
+            DeviceSelector::Path(s.to_string())
+        } else {
+            DeviceSelector::Serial(s.to_string())
+        };
+
+        match &selector {
+            DeviceSelector::Serial(v) | DeviceSelector::Path(v) if v.is_empty() => Err(
+                OcypusError::Config(format!("Invalid device selector: '{}'", s)),
+            ),
+            _ => Ok(selector),
+        }
+    }
+}
+
+impl fmt::Display for DeviceSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceSelector::Serial(serial) => write!(f, "serial:{}", serial),
+            DeviceSelector::Path(path) => write!(f, "path:{}", path),
+        }
+    }
+}
+
+/// How long each source stays on the display in rotation mode, unless
+/// given
+pub const DEFAULT_DWELL: Duration = Duration::from_secs(5);
+
+/// One source in rotation mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotationEntry {
+    pub sensor: SensorType,
+    /// How long the source stays on the display
+    pub dwell: Duration,
+    /// Flag bytes sent instead of the defaults while this source is shown,
+    /// to tell the sources apart
+    pub indicator: Option<[u8; 2]>,
+}
+
+impl RotationEntry {
+    pub fn new(sensor: SensorType) -> Self {
+        Self {
+            sensor,
+            dwell: DEFAULT_DWELL,
+            indicator: None,
+        }
+    }
+}
+
+impl FromStr for RotationEntry {
+    type Err = OcypusError;
+
+    /// Parse `sensor` or `sensor@seconds` (e.g. `gpu@10`)
+    fn from_str(s: &str) -> Result<Self> {
+        let (sensor, dwell) = match s.rsplit_once('@') {
+            Some((sensor, secs)) => {
+                let secs = secs.trim().parse::<u64>().map_err(|e| {
+                    OcypusError::Config(format!("Invalid dwell time in '{}': {}", s, e))
+                })?;
+                (sensor, Duration::from_secs(secs))
+            }
+            None => (s, DEFAULT_DWELL),
+        };
+
+        Ok(Self {
+            dwell,
+            ..Self::new(SensorType::from_str(sensor)?)
+        })
+    }
+}
+
+/// How to retry when the display stops responding
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReconnectPolicy {
+    /// Delay before the second attempt; the first is immediate
+    pub initial_delay: Duration,
+    /// Upper bound for the doubling delay
+    pub max_delay: Duration,
+    /// Fraction of each delay that is randomized away (0.0 to 1.0), so
+    /// several displays don't retry in lockstep
+    pub jitter: f32,
+    /// Give up after this many failed attempts; `None` retries forever
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.2,
+            max_attempts: None,
         }
     }
+}
+
+/// Smoothing applied to readings before they are shown. The defaults pass
+/// readings through unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterConfig {
+    /// Weight of the newest reading in the moving average (0 to 1; 1 turns
+    /// averaging off)
+    pub ema_alpha: f32,
+    /// Number of readings the rolling median covers (1 turns it off)
+    pub median_window: usize,
+    /// How far a reading must move from the shown value before the display
+    /// follows, in the source's unit (°C for temperatures; 0 turns it off)
+    pub hysteresis: f32,
+}
+
+impl Default for FilterConfig {
+    fn default() -> Self {
+        FilterConfig {
+            ema_alpha: 1.0,
+            median_window: 1,
+            hysteresis: 0.0,
+        }
+    }
+}
+
+/// How a reading is brought down to the precision the display shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Drop the fraction (45.9 shows as 45)
+    #[default]
+    Truncate,
+    /// Round to the nearest (45.5 shows as 46)
+    Round,
+    /// Round up (45.1 shows as 46)
+    Ceil,
+}
 
-    pub fn as_str(&self) -> &'static str {
+impl Rounding {
+    /// Round `value` to a whole number
+    pub fn apply(self, value: f32) -> f32 {
         match self {
-            SensorType::Cpu => "cpu",
-            SensorType::Gpu => "gpu",
+            Rounding::Truncate => value.trunc(),
+            Rounding::Round => value.round(),
+            Rounding::Ceil => value.ceil(),
         }
     }
 }
 
+impl FromStr for Rounding {
+    type Err = OcypusError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "truncate" => Ok(Rounding::Truncate),
+            "round" => Ok(Rounding::Round),
+            "ceil" => Ok(Rounding::Ceil),
+            _ => Err(OcypusError::Config(format!(
+                "Invalid rounding: '{}'. Use 'truncate', 'round' or 'ceil'",
+                s
+            ))),
+        }
+    }
+}
+
+/// How readings are laid out on the digits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReadoutConfig {
+    pub rounding: Rounding,
+    /// Show one decimal place (e.g. `45.9`) when the value fits
+    pub decimal: bool,
+    /// Flag bytes that light the decimal point. Which bytes do this hasn't
+    /// been mapped out, so decimal mode needs them set explicitly.
+    pub decimal_flags: Option<[u8; 2]>,
+}
+
+impl ReadoutConfig {
+    /// The flag bytes to show one decimal place with, if decimal mode is on
+    /// and usable
+    pub fn decimal_point(&self) -> Option<[u8; 2]> {
+        self.decimal_flags.filter(|_| self.decimal)
+    }
+}
+
 /// Command line arguments
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(
     author,
     version,
@@ -68,33 +319,115 @@ impl SensorType {
     long_about = "A modern Rust application that monitors system temperature and displays it on an Ocypus Iota L24 digital display."
 )]
 pub struct Args {
-    /// Temperature unit: 'c' for Celsius, 'f' for Fahrenheit
-    #[arg(short, long, default_value = "c")]
-    pub unit: char,
+    /// Configuration file [default: ~/.config/ocypus-digital/config.toml, then /etc/ocypus-digital/config.toml]
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
 
-    /// Temperature update interval in seconds
-    #[arg(short, long, default_value = "1")]
-    pub interval: u64,
+    /// Temperature unit: 'c' for Celsius, 'f' for Fahrenheit [default: c]
+    #[arg(short, long)]
+    pub unit: Option<char>,
 
-    /// High temperature threshold for alerts (°C)
-    #[arg(long, default_value = "80.0")]
-    pub high_threshold: f32,
+    /// Temperature update interval in seconds [default: 1]
+    #[arg(short, long)]
+    pub interval: Option<u64>,
 
-    /// Low temperature threshold for alerts (°C)
-    #[arg(long, default_value = "20.0")]
-    pub low_threshold: f32,
+    /// High temperature threshold for alerts (°C) [default: 80.0]
+    #[arg(long)]
+    pub high_threshold: Option<f32>,
+
+    /// Low temperature threshold for alerts (°C) [default: 20.0]
+    #[arg(long)]
+    pub low_threshold: Option<f32>,
 
     /// Enable temperature threshold alerts
     #[arg(long)]
     pub alerts: bool,
 
-    /// Temperature sensor to use ('cpu', 'gpu')
-    #[arg(short, long, default_value = "cpu")]
-    pub sensor: String,
+    /// Temperature sensor to use ('cpu', 'gpu', 'thermal', 'thermal:<zone type>' or 'thermal:thermal_zone<N>'), or a metric
+    /// such as 'load' or 'fan' (shown in tens of RPM: 145 = 1450 RPM) [default: cpu]
+    #[arg(short, long)]
+    pub sensor: Option<String>,
+
+    /// Cycle the display through several sensors, each shown for SECS seconds
+    /// [default: 5]; repeat for each sensor, in order (e.g. --rotate cpu --rotate gpu@10)
+    #[arg(long, value_name = "SENSOR[@SECS]")]
+    pub rotate: Vec<String>,
+
+    /// Display to drive, by serial ('serial:<serial>') or hidraw path ('/dev/hidrawN');
+    /// overrides any [[display]] tables in the configuration file [default: first match]
+    #[arg(short, long, global = true)]
+    pub device: Option<String>,
+
+    /// Wait for the display to appear instead of exiting when it isn't found at startup
+    #[arg(long)]
+    pub wait_for_device: bool,
+
+    /// List available thermal zones and exit
+    #[arg(long)]
+    pub list_zones: bool,
+
+    /// How readings are rounded to whole digits ('truncate', 'round' or 'ceil') [default: truncate]
+    #[arg(long, value_name = "MODE")]
+    pub rounding: Option<String>,
+
+    /// Show one decimal place (e.g. 45.9) when the value fits; needs
+    /// `decimal_flags` in the configuration file
+    #[arg(long)]
+    pub decimal: bool,
 
     /// Log level (trace, debug, info, warn, error)
-    #[arg(short, long, default_value = "info")]
+    #[arg(short, long, default_value = "info", global = true)]
     pub log_level: String,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Subcommands; without one the monitor runs
+#[derive(Subcommand, Debug, Clone)]
+pub enum Command {
+    /// List HID devices matching the Ocypus VID/PID
+    ListDevices {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// List every temperature source with its current reading
+    Sensors {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
+    /// Dump the report descriptor and the reports the display answers with
+    DeviceInfo {
+        /// Print JSON instead of hex dumps
+        #[arg(long)]
+        json: bool,
+        /// How long to wait for an unsolicited input report, in milliseconds
+        #[arg(long, default_value_t = 500)]
+        timeout_ms: u64,
+    },
+    /// Send an arbitrary report, zero-padded to 64 bytes, for protocol exploration
+    SendRaw {
+        /// Report bytes in hex, starting with the report ID (e.g. "07 ff ff 00 04 02")
+        #[arg(required_unless_present = "file", conflicts_with = "file")]
+        hex: Option<String>,
+        /// Read the report from a file of hex text
+        #[arg(long)]
+        file: Option<PathBuf>,
+        /// Send the file's bytes as they are instead of parsing them as hex
+        #[arg(long, requires = "file")]
+        binary: bool,
+        /// Wait this long for a response after each report, in milliseconds
+        #[arg(long, value_name = "MS")]
+        read: Option<u64>,
+        /// Cycle the byte at this index through 0..=255, one report per step
+        #[arg(long, value_name = "INDEX")]
+        sweep: Option<usize>,
+        /// Delay between sweep steps, in milliseconds
+        #[arg(long, default_value_t = 500)]
+        delay_ms: u64,
+    },
 }
 
 /// Application configuration
@@ -106,6 +439,20 @@ pub struct Config {
     pub low_threshold: f32,
     pub alerts_enabled: bool,
     pub sensor_type: SensorType,
+    /// Sources to cycle through instead of `sensor_type`, if not empty
+    pub rotation: Vec<RotationEntry>,
+    /// Display to drive; the first matching device if `None`
+    pub device: Option<DeviceSelector>,
+    /// Keep retrying at startup until the display is found
+    pub wait_for_device: bool,
+    /// Backoff used when the display stops responding
+    pub reconnect: ReconnectPolicy,
+    /// Smoothing between the sensor and the display
+    pub filter: FilterConfig,
+    /// Rounding and decimal display
+    pub readout: ReadoutConfig,
+    /// Configuration file the values were loaded from, if any
+    pub source: Option<PathBuf>,
 }
 
 impl Default for Config {
@@ -116,38 +463,855 @@ impl Default for Config {
             high_threshold: 80.0,
             low_threshold: 20.0,
             alerts_enabled: false,
-            sensor_type: SensorType::Cpu,
+            sensor_type: SensorType::new("cpu"),
+            rotation: Vec::new(),
+            device: None,
+            wait_for_device: false,
+            reconnect: ReconnectPolicy::default(),
+            filter: FilterConfig::default(),
+            readout: ReadoutConfig::default(),
+            source: None,
+        }
+    }
+}
+
+/// Configuration shared between the monitor thread, the main loop and the
+/// reloader
+pub type SharedConfig = Arc<RwLock<Config>>;
+
+/// Take a copy of the current shared configuration
+pub fn snapshot(config: &SharedConfig) -> Config {
+    config.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Configuration file contents; every key is optional and falls back to
+/// the defaults
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileConfig {
+    pub unit: Option<char>,
+    pub interval: Option<u64>,
+    pub high_threshold: Option<f32>,
+    pub low_threshold: Option<f32>,
+    pub alerts: Option<bool>,
+    pub sensor: Option<String>,
+    /// `[[rotation]]` tables; replaces the inherited rotation if present
+    pub rotation: Option<Vec<RotationFileConfig>>,
+    pub device: Option<String>,
+    pub wait_for_device: Option<bool>,
+    pub reconnect: Option<ReconnectFileConfig>,
+    pub filter: Option<FilterFileConfig>,
+    pub readout: Option<ReadoutFileConfig>,
+    /// Per-display `[[display]]` tables, each overriding the top-level keys
+    #[serde(default)]
+    pub display: Vec<FileConfig>,
+}
+
+/// The `[reconnect]` table of a configuration file
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReconnectFileConfig {
+    pub initial_delay_ms: Option<u64>,
+    pub max_delay_ms: Option<u64>,
+    pub jitter: Option<f32>,
+    /// 0 retries forever
+    pub max_attempts: Option<u32>,
+}
+
+impl ReconnectFileConfig {
+    /// Apply the values set in the table on top of `policy`
+    fn apply(&self, policy: &mut ReconnectPolicy) {
+        if let Some(ms) = self.initial_delay_ms {
+            policy.initial_delay = Duration::from_millis(ms);
+        }
+        if let Some(ms) = self.max_delay_ms {
+            policy.max_delay = Duration::from_millis(ms);
+        }
+        if let Some(jitter) = self.jitter {
+            policy.jitter = jitter;
+        }
+        if let Some(attempts) = self.max_attempts {
+            policy.max_attempts = (attempts > 0).then_some(attempts);
+        }
+    }
+}
+
+/// The `[filter]` table of a configuration file
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FilterFileConfig {
+    pub ema_alpha: Option<f32>,
+    pub median_window: Option<usize>,
+    pub hysteresis: Option<f32>,
+}
+
+impl FilterFileConfig {
+    /// Apply the values set in the table on top of `filter`
+    fn apply(&self, filter: &mut FilterConfig) {
+        if let Some(alpha) = self.ema_alpha {
+            filter.ema_alpha = alpha;
+        }
+        if let Some(window) = self.median_window {
+            filter.median_window = window;
+        }
+        if let Some(band) = self.hysteresis {
+            filter.hysteresis = band;
+        }
+    }
+}
+
+/// The `[readout]` table of a configuration file
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReadoutFileConfig {
+    pub rounding: Option<String>,
+    pub decimal: Option<bool>,
+    /// Two flag bytes in hex (e.g. "ff 7f")
+    pub decimal_flags: Option<String>,
+}
+
+impl ReadoutFileConfig {
+    /// Apply the values set in the table on top of `readout`, naming the
+    /// failing key (`readout.rounding`, ...) through `key_error`
+    fn apply(
+        &self,
+        readout: &mut ReadoutConfig,
+        key_error: impl Fn(&str, OcypusError) -> OcypusError,
+    ) -> Result<()> {
+        if let Some(rounding) = &self.rounding {
+            readout.rounding =
+                Rounding::from_str(rounding).map_err(|e| key_error("readout.rounding", e))?;
+        }
+        if let Some(decimal) = self.decimal {
+            readout.decimal = decimal;
+        }
+        if let Some(flags) = &self.decimal_flags {
+            let bytes = crate::protocol::parse_hex(flags)
+                .map_err(|e| key_error("readout.decimal_flags", e))?;
+            readout.decimal_flags = Some(bytes.try_into().map_err(|bytes: Vec<u8>| {
+                key_error(
+                    "readout.decimal_flags",
+                    OcypusError::Config(format!("must be 2 bytes, got {}", bytes.len())),
+                )
+            })?);
+        }
+        Ok(())
+    }
+}
+
+/// A `[[rotation]]` table of a configuration file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RotationFileConfig {
+    pub sensor: String,
+    pub dwell_secs: Option<u64>,
+    /// Two flag bytes in hex (e.g. "ff fe")
+    pub indicator: Option<String>,
+}
+
+impl RotationFileConfig {
+    fn to_entry(&self) -> Result<RotationEntry> {
+        let mut entry = RotationEntry::new(SensorType::from_str(&self.sensor)?);
+        if let Some(secs) = self.dwell_secs {
+            entry.dwell = Duration::from_secs(secs);
         }
+        if let Some(indicator) = &self.indicator {
+            let bytes = crate::protocol::parse_hex(indicator)?;
+            entry.indicator = Some(bytes.try_into().map_err(|bytes: Vec<u8>| {
+                OcypusError::Config(format!("indicator must be 2 bytes, got {}", bytes.len()))
+            })?);
+        }
+        Ok(entry)
+    }
+}
+
+impl FileConfig {
+    /// Parse a configuration file from TOML text
+    pub fn parse(text: &str) -> Result<Self> {
+        toml::from_str(text).map_err(|e| OcypusError::Config(e.to_string()))
+    }
+
+    /// Read and parse a configuration file
+    pub fn from_path(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|e| {
+            OcypusError::Config(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+
+        Self::parse(&text)
+            .map_err(|e| OcypusError::Config(format!("{}: {}", path.display(), e.message())))
+    }
+
+    /// Apply the values set in the file on top of `config`
+    pub fn apply(&self, config: &mut Config) -> Result<()> {
+        self.apply_keys(config, "")
+    }
+
+    /// Apply values, naming keys with `prefix` in errors (e.g. `display[1].`)
+    fn apply_keys(&self, config: &mut Config, prefix: &str) -> Result<()> {
+        let key_error = |key: &str, e: OcypusError| {
+            OcypusError::Config(format!(
+                "invalid value for key `{}{}`: {}",
+                prefix,
+                key,
+                e.message()
+            ))
+        };
+
+        if let Some(unit) = self.unit {
+            config.temperature_unit =
+                TemperatureUnit::from_char(unit).map_err(|e| key_error("unit", e))?;
+        }
+        if let Some(interval) = self.interval {
+            config.update_interval = Duration::from_secs(interval);
+        }
+        if let Some(high) = self.high_threshold {
+            config.high_threshold = high;
+        }
+        if let Some(low) = self.low_threshold {
+            config.low_threshold = low;
+        }
+        if let Some(alerts) = self.alerts {
+            config.alerts_enabled = alerts;
+        }
+        if let Some(sensor) = &self.sensor {
+            config.sensor_type =
+                SensorType::from_str(sensor).map_err(|e| key_error("sensor", e))?;
+        }
+        if let Some(rotation) = &self.rotation {
+            config.rotation = rotation
+                .iter()
+                .enumerate()
+                .map(|(i, entry)| {
+                    entry
+                        .to_entry()
+                        .map_err(|e| key_error(&format!("rotation[{}]", i), e))
+                })
+                .collect::<Result<_>>()?;
+        }
+        if let Some(device) = &self.device {
+            config.device =
+                Some(DeviceSelector::from_str(device).map_err(|e| key_error("device", e))?);
+        }
+        if let Some(wait) = self.wait_for_device {
+            config.wait_for_device = wait;
+        }
+        if let Some(reconnect) = &self.reconnect {
+            reconnect.apply(&mut config.reconnect);
+        }
+        if let Some(filter) = &self.filter {
+            filter.apply(&mut config.filter);
+        }
+        if let Some(readout) = &self.readout {
+            readout.apply(&mut config.readout, key_error)?;
+        }
+        Ok(())
     }
 }
 
+/// Find the first existing configuration file on the default search path
+pub fn find_config_file() -> Option<PathBuf> {
+    let user_config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .map(|dir| dir.join("ocypus-digital").join("config.toml"));
+
+    user_config
+        .into_iter()
+        .chain(std::iter::once(PathBuf::from(SYSTEM_CONFIG_PATH)))
+        .find(|path| path.is_file())
+}
+
 impl Config {
-    /// Create configuration from command line arguments
+    /// Load configuration from the defaults, then the configuration file
+    /// (`--config` or the default search path), then command line flags
+    pub fn load(args: &Args) -> Result<Self> {
+        let (mut config, _) = Self::load_file(args)?;
+        config.apply_args(args)?;
+        Ok(config)
+    }
+
+    /// Load one configuration per display.
+    ///
+    /// `--device` selects a single display. Otherwise each `[[display]]`
+    /// table inherits the top-level values of the file and overrides its own
+    /// keys; without tables, a single display uses the top-level values.
+    /// Command line flags override every display.
+    pub fn load_displays(args: &Args) -> Result<Vec<Self>> {
+        let (base, tables) = Self::load_file(args)?;
+
+        if tables.is_empty() || args.device.is_some() {
+            let mut config = base;
+            config.apply_args(args)?;
+            return Ok(vec![config]);
+        }
+
+        let in_file = |e: OcypusError| match &base.source {
+            Some(path) => OcypusError::Config(format!("{}: {}", path.display(), e.message())),
+            None => e,
+        };
+
+        let mut displays: Vec<Config> = Vec::with_capacity(tables.len());
+        for (index, table) in tables.iter().enumerate() {
+            let prefix = format!("display[{}].", index);
+
+            if !table.display.is_empty() {
+                return Err(in_file(OcypusError::Config(format!(
+                    "`{}display` tables cannot be nested",
+                    prefix
+                ))));
+            }
+
+            let mut config = base.clone();
+            table.apply_keys(&mut config, &prefix).map_err(in_file)?;
+            config.apply_args(args)?;
+
+            if tables.len() > 1 && config.device.is_none() {
+                return Err(in_file(OcypusError::Config(format!(
+                    "`{}device` is required when more than one display is configured",
+                    prefix
+                ))));
+            }
+            if let Some(other) = displays.iter().position(|d| d.device == config.device) {
+                return Err(in_file(OcypusError::Config(format!(
+                    "`{}device` duplicates `display[{}].device`",
+                    prefix, other
+                ))));
+            }
+
+            displays.push(config);
+        }
+
+        Ok(displays)
+    }
+
+    /// Apply the configuration file's top-level keys to the defaults and
+    /// return its `[[display]]` tables
+    fn load_file(args: &Args) -> Result<(Self, Vec<FileConfig>)> {
+        let mut config = Config::default();
+
+        let Some(path) = args.config.clone().or_else(find_config_file) else {
+            return Ok((config, Vec::new()));
+        };
+
+        let file = FileConfig::from_path(&path)?;
+        file.apply(&mut config)
+            .map_err(|e| OcypusError::Config(format!("{}: {}", path.display(), e.message())))?;
+        config.source = Some(path);
+
+        Ok((config, file.display))
+    }
+
+    /// Create configuration from command line arguments only
     pub fn from_args(args: &Args) -> Result<Self> {
-        Ok(Config {
-            temperature_unit: TemperatureUnit::from_char(args.unit)?,
-            update_interval: Duration::from_secs(args.interval),
-            high_threshold: args.high_threshold,
-            low_threshold: args.low_threshold,
-            alerts_enabled: args.alerts,
-            sensor_type: SensorType::from_str(&args.sensor)?,
-        })
+        let mut config = Config::default();
+        config.apply_args(args)?;
+        Ok(config)
+    }
+
+    /// Override configuration with the flags given on the command line
+    fn apply_args(&mut self, args: &Args) -> Result<()> {
+        if let Some(unit) = args.unit {
+            self.temperature_unit = TemperatureUnit::from_char(unit)?;
+        }
+        if let Some(interval) = args.interval {
+            self.update_interval = Duration::from_secs(interval);
+        }
+        if let Some(high) = args.high_threshold {
+            self.high_threshold = high;
+        }
+        if let Some(low) = args.low_threshold {
+            self.low_threshold = low;
+        }
+        if args.alerts {
+            self.alerts_enabled = true;
+        }
+        if let Some(sensor) = &args.sensor {
+            self.sensor_type = SensorType::from_str(sensor)?;
+            // An explicit sensor wins over a rotation from the file
+            self.rotation.clear();
+        }
+        if !args.rotate.is_empty() {
+            self.rotation = args
+                .rotate
+                .iter()
+                .map(|entry| RotationEntry::from_str(entry))
+                .collect::<Result<_>>()?;
+        }
+        if let Some(device) = &args.device {
+            self.device = Some(DeviceSelector::from_str(device)?);
+        }
+        if args.wait_for_device {
+            self.wait_for_device = true;
+        }
+        if let Some(rounding) = &args.rounding {
+            self.readout.rounding = Rounding::from_str(rounding)?;
+        }
+        if args.decimal {
+            self.readout.decimal = true;
+        }
+        Ok(())
     }
 
     /// Validate configuration and return self for chaining
     pub fn validate(self) -> Result<Self> {
         if self.high_threshold <= self.low_threshold {
+            return Err(OcypusError::Config(format!(
+                "high_threshold ({:.1}) must be greater than low_threshold ({:.1})",
+                self.high_threshold, self.low_threshold
+            )));
+        }
+
+        if self.update_interval.as_secs() == 0 {
             return Err(OcypusError::Config(
-                "High threshold must be greater than low threshold".to_string(),
+                "interval must be greater than 0 seconds".to_string(),
             ));
         }
 
-        if self.update_interval.as_secs() == 0 {
+        if self.reconnect.initial_delay > self.reconnect.max_delay {
+            return Err(OcypusError::Config(format!(
+                "reconnect.initial_delay_ms ({}) must not exceed reconnect.max_delay_ms ({})",
+                self.reconnect.initial_delay.as_millis(),
+                self.reconnect.max_delay.as_millis()
+            )));
+        }
+
+        if !(self.filter.ema_alpha > 0.0 && self.filter.ema_alpha <= 1.0) {
+            return Err(OcypusError::Config(format!(
+                "filter.ema_alpha ({}) must be greater than 0.0 and at most 1.0",
+                self.filter.ema_alpha
+            )));
+        }
+
+        if self.filter.median_window == 0 {
             return Err(OcypusError::Config(
-                "Update interval must be greater than 0 seconds".to_string(),
+                "filter.median_window must be at least 1".to_string(),
             ));
         }
 
+        if self.filter.hysteresis.is_nan() || self.filter.hysteresis < 0.0 {
+            return Err(OcypusError::Config(format!(
+                "filter.hysteresis ({}) must not be negative",
+                self.filter.hysteresis
+            )));
+        }
+
+        if let Some(entry) = self.rotation.iter().find(|e| e.dwell.is_zero()) {
+            return Err(OcypusError::Config(format!(
+                "rotation dwell time for sensor {} must be greater than 0 seconds",
+                entry.sensor
+            )));
+        }
+
+        if !(0.0..=1.0).contains(&self.reconnect.jitter) {
+            return Err(OcypusError::Config(format!(
+                "reconnect.jitter ({}) must be between 0.0 and 1.0",
+                self.reconnect.jitter
+            )));
+        }
+
         Ok(self)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    fn args(extra: &[&str]) -> Args {
+        let mut argv = vec!["ocypus-l24-digital"];
+        argv.extend_from_slice(extra);
+        Args::parse_from(argv)
+    }
+
+    fn config_file(text: &str) -> NamedTempFile {
+        let file = NamedTempFile::new().unwrap();
+        fs::write(file.path(), text).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_file_config_values() {
+        let file = config_file(
+            r#"
+            unit = "f"
+            interval = 3
+            high_threshold = 85.0
+            low_threshold = 15.0
+            alerts = true
+            sensor = "thermal:x86_pkg_temp"
+            "#,
+        );
+
+        let config = Config::load(&args(&["--config", file.path().to_str().unwrap()])).unwrap();
+        assert_eq!(config.temperature_unit, TemperatureUnit::Fahrenheit);
+        assert_eq!(config.update_interval, Duration::from_secs(3));
+        assert_eq!(config.high_threshold, 85.0);
+        assert_eq!(config.low_threshold, 15.0);
+        assert!(config.alerts_enabled);
+        assert_eq!(config.sensor_type.to_string(), "thermal:x86_pkg_temp");
+        assert_eq!(config.source.as_deref(), Some(file.path()));
+    }
+
+    #[test]
+    fn test_cli_overrides_file() {
+        let file = config_file("unit = \"f\"\ninterval = 5\nsensor = \"gpu\"\n");
+        let path = file.path().to_str().unwrap();
+
+        let config = Config::load(&args(&["--config", path, "-i", "2", "-s", "cpu"])).unwrap();
+        assert_eq!(config.temperature_unit, TemperatureUnit::Fahrenheit);
+        assert_eq!(config.update_interval, Duration::from_secs(2));
+        assert_eq!(config.sensor_type, SensorType::new("cpu"));
+    }
+
+    #[test]
+    fn test_file_errors_name_the_key() {
+        let err = FileConfig::parse("interval = \"fast\"")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("interval"), "unexpected error: {}", err);
+
+        let err = FileConfig::parse("colour = \"red\"")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("colour"), "unexpected error: {}", err);
+
+        let mut config = Config::default();
+        let err = FileConfig::parse("unit = \"k\"")
+            .unwrap()
+            .apply(&mut config)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("`unit`"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_device_selector_parsing() {
+        assert_eq!(
+            DeviceSelector::from_str("serial:ABC123").unwrap(),
+            DeviceSelector::Serial("ABC123".to_string())
+        );
+        assert_eq!(
+            DeviceSelector::from_str("/dev/hidraw3").unwrap(),
+            DeviceSelector::Path("/dev/hidraw3".to_string())
+        );
+        assert_eq!(
+            DeviceSelector::from_str("path:1-2:1.0").unwrap(),
+            DeviceSelector::Path("1-2:1.0".to_string())
+        );
+        assert_eq!(
+            DeviceSelector::from_str("ABC123").unwrap(),
+            DeviceSelector::Serial("ABC123".to_string())
+        );
+        assert!(DeviceSelector::from_str("serial:").is_err());
+    }
+
+    #[test]
+    fn test_display_tables() {
+        let file = config_file(
+            r#"
+            unit = "f"
+            interval = 2
+
+            [[display]]
+            device = "serial:CPU0001"
+            sensor = "cpu"
+
+            [[display]]
+            device = "/dev/hidraw5"
+            sensor = "gpu"
+            unit = "c"
+            "#,
+        );
+        let path = file.path().to_str().unwrap();
+
+        let displays = Config::load_displays(&args(&["--config", path])).unwrap();
+        assert_eq!(displays.len(), 2);
+        assert_eq!(
+            displays[0].device,
+            Some(DeviceSelector::Serial("CPU0001".to_string()))
+        );
+        assert_eq!(displays[0].temperature_unit, TemperatureUnit::Fahrenheit);
+        assert_eq!(displays[1].sensor_type, SensorType::new("gpu"));
+        assert_eq!(displays[1].temperature_unit, TemperatureUnit::Celsius);
+        assert_eq!(displays[1].update_interval, Duration::from_secs(2));
+
+        // --device selects a single display and ignores the tables
+        let displays =
+            Config::load_displays(&args(&["--config", path, "--device", "serial:X"])).unwrap();
+        assert_eq!(displays.len(), 1);
+        assert_eq!(displays[0].sensor_type, SensorType::new("cpu"));
+    }
+
+    #[test]
+    fn test_display_table_errors() {
+        let file = config_file("[[display]]\nsensor = \"cpu\"\n[[display]]\nsensor = \"gpu\"\n");
+        let err = Config::load_displays(&args(&["--config", file.path().to_str().unwrap()]))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("display[0].device"),
+            "unexpected error: {}",
+            err
+        );
+
+        let file = config_file("[[display]]\ndevice = \"A\"\n[[display]]\ndevice = \"serial:A\"\n");
+        let err = Config::load_displays(&args(&["--config", file.path().to_str().unwrap()]))
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("display[1].device"),
+            "unexpected error: {}",
+            err
+        );
+
+        let file = config_file("[[display]]\ndevice = \"A\"\nunit = \"x\"\n");
+        let err = Config::load_displays(&args(&["--config", file.path().to_str().unwrap()]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("display[0].unit"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_file_error_message() {
+        let file = config_file("unit = \"k\"\n");
+        let path = file.path().to_str().unwrap();
+        let err = Config::load_displays(&args(&["--config", path])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Configuration error: {}: invalid value for key `unit`: \
+                 Invalid temperature unit: 'k'. Use 'c' or 'f'",
+                path
+            )
+        );
+
+        let file = config_file("[[display]]\ndevice = \"A\"\nunit = \"k\"\n");
+        let path = file.path().to_str().unwrap();
+        let err = Config::load_displays(&args(&["--config", path])).unwrap_err();
+        assert_eq!(
+            err.message(),
+            format!(
+                "{}: invalid value for key `display[0].unit`: \
+                 Invalid temperature unit: 'k'. Use 'c' or 'f'",
+                path
+            )
+        );
+    }
+
+    #[test]
+    fn test_reconnect_settings() {
+        let file = config_file(
+            r#"
+            wait_for_device = true
+
+            [reconnect]
+            initial_delay_ms = 250
+            max_delay_ms = 10000
+            jitter = 0.5
+            max_attempts = 0
+            "#,
+        );
+
+        let config = Config::load(&args(&["--config", file.path().to_str().unwrap()]))
+            .unwrap()
+            .validate()
+            .unwrap();
+        assert!(config.wait_for_device);
+        assert_eq!(config.reconnect.initial_delay, Duration::from_millis(250));
+        assert_eq!(config.reconnect.max_delay, Duration::from_secs(10));
+        assert_eq!(config.reconnect.jitter, 0.5);
+        assert_eq!(config.reconnect.max_attempts, None);
+
+        let file = config_file("[reconnect]\ninitial_delay_ms = 5000\nmax_delay_ms = 1000\n");
+        let config = Config::load(&args(&["--config", file.path().to_str().unwrap()])).unwrap();
+        assert!(config.validate().is_err());
+
+        let config = Config {
+            reconnect: ReconnectPolicy {
+                jitter: 1.5,
+                ..ReconnectPolicy::default()
+            },
+            ..Config::default()
+        };
+        assert!(config.validate().is_err());
+        assert!(
+            Config::from_args(&args(&["--wait-for-device"]))
+                .unwrap()
+                .wait_for_device
+        );
+    }
+
+    #[test]
+    fn test_missing_explicit_config_file() {
+        let result = Config::load(&args(&["--config", "/nonexistent/ocypus.toml"]));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_rotation_settings() {
+        let file = config_file(
+            r#"
+            sensor = "cpu"
+
+            [[rotation]]
+            sensor = "cpu"
+            indicator = "ff fe"
+
+            [[rotation]]
+            sensor = "hwmon:nvme/Composite"
+            dwell_secs = 10
+            "#,
+        );
+        let path = file.path().to_str().unwrap();
+
+        let config = Config::load(&args(&["--config", path])).unwrap();
+        assert_eq!(
+            config.rotation,
+            vec![
+                RotationEntry {
+                    sensor: SensorType::new("cpu"),
+                    dwell: DEFAULT_DWELL,
+                    indicator: Some([0xff, 0xfe]),
+                },
+                RotationEntry {
+                    sensor: SensorType::from_str("hwmon:nvme/Composite").unwrap(),
+                    dwell: Duration::from_secs(10),
+                    indicator: None,
+                },
+            ]
+        );
+
+        // --rotate replaces the file's rotation, --sensor turns it off
+        let config = Config::load(&args(&["--config", path, "--rotate", "gpu@3"])).unwrap();
+        assert_eq!(config.rotation.len(), 1);
+        assert_eq!(config.rotation[0].dwell, Duration::from_secs(3));
+        let config = Config::load(&args(&["--config", path, "-s", "gpu"])).unwrap();
+        assert!(config.rotation.is_empty());
+
+        assert!(RotationEntry::from_str("cpu@soon").is_err());
+        let config = Config::from_args(&args(&["--rotate", "cpu@0"])).unwrap();
+        assert!(config.validate().is_err());
+
+        let file = config_file(
+            "[[rotation]]
+sensor = \"cpu\"
+indicator = \"ff\"
+",
+        );
+        let err = Config::load(&args(&["--config", file.path().to_str().unwrap()]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("rotation[0]"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_filter_settings() {
+        let file = config_file(
+            r#"
+            [filter]
+            ema_alpha = 0.3
+            median_window = 5
+            hysteresis = 0.6
+
+            [[display]]
+            device = "serial:A"
+
+            [[display]]
+            device = "serial:B"
+            filter = { hysteresis = 0.0 }
+            "#,
+        );
+        let path = file.path().to_str().unwrap();
+
+        let displays = Config::load_displays(&args(&["--config", path])).unwrap();
+        assert_eq!(
+            displays[0].filter,
+            FilterConfig {
+                ema_alpha: 0.3,
+                median_window: 5,
+                hysteresis: 0.6,
+            }
+        );
+        assert_eq!(displays[1].filter.hysteresis, 0.0);
+        assert_eq!(displays[1].filter.median_window, 5);
+
+        for filter in [
+            FilterConfig {
+                ema_alpha: 0.0,
+                ..FilterConfig::default()
+            },
+            FilterConfig {
+                median_window: 0,
+                ..FilterConfig::default()
+            },
+            FilterConfig {
+                hysteresis: -1.0,
+                ..FilterConfig::default()
+            },
+        ] {
+            let config = Config {
+                filter,
+                ..Config::default()
+            };
+            assert!(config.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn test_readout_settings() {
+        let file = config_file(
+            r#"
+            [readout]
+            rounding = "round"
+            decimal = true
+            decimal_flags = "ff 7f"
+            "#,
+        );
+        let path = file.path().to_str().unwrap();
+
+        let config = Config::load(&args(&["--config", path])).unwrap();
+        assert_eq!(
+            config.readout,
+            ReadoutConfig {
+                rounding: Rounding::Round,
+                decimal: true,
+                decimal_flags: Some([0xff, 0x7f]),
+            }
+        );
+        assert_eq!(config.readout.decimal_point(), Some([0xff, 0x7f]));
+
+        let config = Config::load(&args(&["--config", path, "--rounding", "ceil"])).unwrap();
+        assert_eq!(config.readout.rounding, Rounding::Ceil);
+
+        // --decimal alone has nothing to light the decimal point with
+        let config = Config::from_args(&args(&["--decimal"])).unwrap();
+        assert!(config.readout.decimal);
+        assert_eq!(config.readout.decimal_point(), None);
+
+        assert!(Config::from_args(&args(&["--rounding", "floor"])).is_err());
+        let file = config_file("[readout]\ndecimal_flags = \"7f\"\n");
+        let err = Config::load(&args(&["--config", file.path().to_str().unwrap()]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("readout"), "unexpected error: {}", err);
+
+        let mut config = Config::default();
+        let apply = |text: &str, config: &mut Config| {
+            FileConfig::parse(text)
+                .unwrap()
+                .apply(config)
+                .unwrap_err()
+                .message()
+        };
+        assert_eq!(
+            apply("[readout]\nrounding = \"floor\"\n", &mut config),
+            "invalid value for key `readout.rounding`: \
+             Invalid rounding: 'floor'. Use 'truncate', 'round' or 'ceil'"
+        );
+        assert_eq!(
+            apply("[readout]\ndecimal_flags = \"7f\"\n", &mut config),
+            "invalid value for key `readout.decimal_flags`: must be 2 bytes, got 1"
+        );
+    }
+}
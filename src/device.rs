@@ -1,47 +1,217 @@
-use crate::config::{PID, REPORT_ID, REPORT_LENGTH, VID};
+use crate::config::{DeviceSelector, ReadoutConfig, ReconnectPolicy, PID, REPORT_LENGTH, VID};
 use crate::error::{OcypusError, Result};
-use hidapi::HidApi;
+use crate::hotplug::{HotplugEvent, HotplugSource};
+use crate::protocol::{Report, MAX_VALUE};
+use crate::sensor::{Measurement, MetricUnit};
+use crate::transport::{HidApiTransport, HidConnection, HidTransport};
 use log::{debug, info, warn};
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Largest HID report descriptor the kernel hands out
+const MAX_REPORT_DESCRIPTOR_SIZE: usize = 4096;
+
+/// How long to keep trying to open a newly added hidraw node; the kernel
+/// announces it before udev has finished setting it up
+const HOTPLUG_SETTLE_TIME: Duration = Duration::from_secs(5);
+
+/// Fan speeds are shown in tens of RPM (1450 RPM reads 145), so that speeds
+/// up to 9990 RPM fit the three digits
+const RPM_PER_UNIT: f32 = 10.0;
+
+/// A HID device as seen during enumeration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DeviceInfo {
+    /// Platform device path (e.g. `/dev/hidraw3`)
+    pub path: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+    /// USB serial number, if the device reports one
+    pub serial: Option<String>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+    /// USB interface number, or -1 if unknown
+    pub interface_number: i32,
+    /// Device release number in binary-coded decimal
+    pub release_number: u16,
+}
+
+impl DeviceInfo {
+    /// Check whether this device is the one `selector` refers to
+    pub fn matches(&self, selector: &DeviceSelector) -> bool {
+        match selector {
+            DeviceSelector::Serial(serial) => self.serial.as_deref() == Some(serial.as_str()),
+            DeviceSelector::Path(path) => &self.path == path,
+        }
+    }
+}
+
+impl From<&hidapi::DeviceInfo> for DeviceInfo {
+    fn from(d: &hidapi::DeviceInfo) -> Self {
+        Self {
+            path: d.path().to_string_lossy().into_owned(),
+            vendor_id: d.vendor_id(),
+            product_id: d.product_id(),
+            serial: d.serial_number().map(str::to_string),
+            manufacturer: d.manufacturer_string().map(str::to_string),
+            product: d.product_string().map(str::to_string),
+            interface_number: d.interface_number(),
+            release_number: d.release_number(),
+        }
+    }
+}
+
+/// List the devices matching the Ocypus VID/PID
+pub fn list_devices(transport: &mut dyn HidTransport) -> Result<Vec<DeviceInfo>> {
+    Ok(transport
+        .enumerate()?
+        .into_iter()
+        .filter(|d| d.vendor_id == VID && d.product_id == PID)
+        .collect())
+}
+
+/// Delays between connection attempts under a `ReconnectPolicy`
+pub struct Backoff {
+    policy: ReconnectPolicy,
+    attempt: u32,
+    rng: u64,
+}
+
+impl Backoff {
+    /// Start a new series of attempts
+    pub fn new(policy: ReconnectPolicy) -> Self {
+        // Randomly keyed hasher, so jitter differs between processes and
+        // displays without pulling in a random number crate
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(std::process::id());
+        Self {
+            policy,
+            attempt: 0,
+            rng: hasher.finish() | 1,
+        }
+    }
+
+    /// Delay before the next attempt: the initial delay doubled for every
+    /// previous attempt, capped at the maximum, minus up to `jitter` of it
+    pub fn next_delay(&mut self) -> Duration {
+        let base = self
+            .policy
+            .initial_delay
+            .saturating_mul(2u32.saturating_pow(self.attempt))
+            .min(self.policy.max_delay);
+        self.attempt = self.attempt.saturating_add(1);
+
+        base.mul_f32(1.0 - self.policy.jitter * self.next_random())
+    }
+
+    /// xorshift64, scaled to [0, 1)
+    fn next_random(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
 
 /// Device communication handler
 pub struct DeviceManager {
-    api: HidApi,
-    device: Option<hidapi::HidDevice>,
+    transport: Box<dyn HidTransport>,
+    device: Option<Box<dyn HidConnection>>,
+    selector: Option<DeviceSelector>,
+    reconnect_policy: ReconnectPolicy,
+    connected: Option<DeviceInfo>,
+    hotplug: Option<Box<dyn HotplugSource>>,
+    unplugged: bool,
+    settle_until: Option<Instant>,
+    last_reopen: Instant,
 }
 
 impl DeviceManager {
-    /// Create a new device manager
+    /// Create a new device manager that connects to the first matching device
     pub fn new() -> Result<Self> {
-        let api = HidApi::new().map_err(|e| OcypusError::HidApi(e.to_string()))?;
-        Ok(Self { api, device: None })
+        Self::with_selector(None)
+    }
+
+    /// Create a new device manager for the device matching `selector`
+    pub fn with_selector(selector: Option<DeviceSelector>) -> Result<Self> {
+        Ok(Self::with_transport(
+            Box::new(HidApiTransport::new()?),
+            selector,
+        ))
+    }
+
+    /// Create a device manager that talks to devices through `transport`
+    pub fn with_transport(
+        transport: Box<dyn HidTransport>,
+        selector: Option<DeviceSelector>,
+    ) -> Self {
+        Self {
+            transport,
+            device: None,
+            selector,
+            reconnect_policy: ReconnectPolicy::default(),
+            connected: None,
+            hotplug: None,
+            unplugged: false,
+            settle_until: None,
+            last_reopen: Instant::now(),
+        }
+    }
+
+    /// Set the backoff used by `reconnect_with_backoff` and `wait_for_device`
+    pub fn set_reconnect_policy(&mut self, policy: ReconnectPolicy) {
+        self.reconnect_policy = policy;
+    }
+
+    /// List every connected device matching the Ocypus VID/PID
+    pub fn enumerate(&mut self) -> Result<Vec<DeviceInfo>> {
+        list_devices(self.transport.as_mut())
     }
 
     /// Connect to the Ocypus device
     pub fn connect(&mut self) -> Result<()> {
         info!("Scanning for Ocypus Iota L24 device...");
+        self.open_matching()
+    }
 
-        for device_info in self.api.device_list() {
-            if device_info.vendor_id() == VID && device_info.product_id() == PID {
-                let path = device_info.path().to_string_lossy().into_owned();
-                debug!("Found device at: {}", path);
+    /// Open the first device matching the selector
+    fn open_matching(&mut self) -> Result<()> {
+        let selector = self.selector.clone();
+        for device_info in self.enumerate()? {
+            if let Some(selector) = &selector {
+                if !device_info.matches(selector) {
+                    debug!(
+                        "Skipping device at {} (want {})",
+                        device_info.path, selector
+                    );
+                    continue;
+                }
+            }
 
-                match self.api.open_path(device_info.path()) {
-                    Ok(dev) => {
-                        info!("Connected to Ocypus Iota L24 at {}", path);
-                        self.device = Some(dev);
-                        return Ok(());
-                    }
-                    Err(e) => {
-                        warn!("Failed to open device at {}: {}", path, e);
-                        continue;
-                    }
+            debug!("Found device at: {}", device_info.path);
+            match self.transport.open(&device_info.path) {
+                Ok(dev) => {
+                    info!("Connected to Ocypus Iota L24 at {}", device_info.path);
+                    self.device = Some(dev);
+                    self.connected = Some(device_info);
+                    self.unplugged = false;
+                    self.settle_until = None;
+                    return Ok(());
+                }
+                Err(e) => {
+                    warn!("Failed to open device at {}: {}", device_info.path, e);
+                    continue;
                 }
             }
         }
 
-        Err(OcypusError::Device(
-            "No Ocypus Iota L24 device found".to_string(),
-        ))
+        Err(OcypusError::Device(match &self.selector {
+            Some(selector) => format!("No Ocypus Iota L24 device found matching {}", selector),
+            None => "No Ocypus Iota L24 device found".to_string(),
+        }))
     }
 
     /// Send temperature data to the device
@@ -50,14 +220,29 @@ impl DeviceManager {
         temp_celsius: f32,
         unit: crate::config::TemperatureUnit,
     ) -> Result<()> {
-        let device = self
-            .device
-            .as_mut()
-            .ok_or_else(|| OcypusError::Device("Device not connected".to_string()))?;
+        self.send_measurement(Measurement::celsius(temp_celsius), unit)
+    }
+
+    /// Send a reading of any metric to the device
+    pub fn send_measurement(
+        &mut self,
+        measurement: Measurement,
+        unit: crate::config::TemperatureUnit,
+    ) -> Result<()> {
+        let report = build_report(measurement, unit)?;
+        self.send_raw(&report)
+    }
+
+    /// Send a typed report to the device
+    pub fn send_report(&mut self, report: &Report) -> Result<()> {
+        self.send_raw(&report.encode())
+    }
 
-        let report = build_temperature_report(temp_celsius, unit)?;
+    /// Write a complete output report to the device
+    pub fn send_raw(&mut self, report: &[u8; REPORT_LENGTH]) -> Result<()> {
+        let device = self.connection()?;
 
-        match device.write(&report) {
+        match device.write(report) {
             Ok(bytes_written) => {
                 debug!("Sent {} bytes to device", bytes_written);
                 if bytes_written != REPORT_LENGTH {
@@ -76,59 +261,520 @@ impl DeviceManager {
     }
 
     /// Check if device is connected
-    #[allow(unused)]
     pub fn is_connected(&self) -> bool {
         self.device.is_some()
     }
 
+    /// The device currently open, if any
+    pub fn connected_device(&self) -> Option<&DeviceInfo> {
+        self.connected.as_ref()
+    }
+
+    fn connection(&mut self) -> Result<&mut Box<dyn HidConnection>> {
+        self.device
+            .as_mut()
+            .ok_or_else(|| OcypusError::Device("Device not connected".to_string()))
+    }
+
+    /// Wait up to `timeout` for an input report on the interrupt endpoint
+    pub fn read_report(&mut self, timeout: Duration) -> Result<Option<Vec<u8>>> {
+        let mut buf = [0u8; REPORT_LENGTH + 1];
+        let timeout_ms = i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX);
+        let len = self.connection()?.read_timeout(&mut buf, timeout_ms)?;
+        Ok((len > 0).then(|| buf[..len].to_vec()))
+    }
+
+    /// Request input report `report_id` over the control endpoint
+    pub fn get_input_report(&mut self, report_id: u8) -> Result<Vec<u8>> {
+        let mut buf = [0u8; REPORT_LENGTH + 1];
+        buf[0] = report_id;
+        let len = self.connection()?.get_input_report(&mut buf)?;
+        Ok(buf[..len].to_vec())
+    }
+
+    /// Request feature report `report_id`
+    pub fn get_feature_report(&mut self, report_id: u8) -> Result<Vec<u8>> {
+        let mut buf = [0u8; REPORT_LENGTH + 1];
+        buf[0] = report_id;
+        let len = self.connection()?.get_feature_report(&mut buf)?;
+        Ok(buf[..len].to_vec())
+    }
+
+    /// Read the device's HID report descriptor
+    pub fn report_descriptor(&mut self) -> Result<Vec<u8>> {
+        let mut buf = [0u8; MAX_REPORT_DESCRIPTOR_SIZE];
+        let len = self.connection()?.get_report_descriptor(&mut buf)?;
+        Ok(buf[..len].to_vec())
+    }
+
     /// Reconnect to the device
     pub fn reconnect(&mut self) -> Result<()> {
         info!("Attempting to reconnect to device...");
         self.device = None;
         self.connect()
     }
+
+    /// React to hidraw add/remove and resume events from `source`; see
+    /// `poll_hotplug`
+    pub fn set_hotplug(&mut self, source: Box<dyn HotplugSource>) {
+        self.hotplug = Some(source);
+    }
+
+    /// Check whether the display was unplugged and hasn't come back yet
+    pub fn is_unplugged(&self) -> bool {
+        self.unplugged
+    }
+
+    /// Handle pending hotplug events: forget the display when its node is
+    /// removed, and reopen it as soon as a node is added or the system
+    /// resumes. While unplugged, a reopen is also tried every
+    /// `max_delay` in case an event was missed.
+    pub fn poll_hotplug(&mut self) {
+        let Some(source) = self.hotplug.as_mut() else {
+            return;
+        };
+
+        for event in source.poll() {
+            match event {
+                HotplugEvent::Removed(path)
+                    if self.connected.as_ref().is_some_and(|d| d.path == path) =>
+                {
+                    info!("Display at {} unplugged; waiting for it to return", path);
+                    self.device = None;
+                    self.connected = None;
+                    self.unplugged = true;
+                }
+                HotplugEvent::Removed(path) => debug!("hidraw device {} removed", path),
+                HotplugEvent::Added(path) => {
+                    debug!("hidraw device {} added", path);
+                    if !self.is_connected() {
+                        self.settle_until = Some(Instant::now() + HOTPLUG_SETTLE_TIME);
+                    }
+                }
+                HotplugEvent::Resumed => {
+                    // The display may have been reset while suspended, so
+                    // don't trust the old handle
+                    info!("Reopening display after resume");
+                    self.device = None;
+                    self.connected = None;
+                    self.settle_until = Some(Instant::now() + HOTPLUG_SETTLE_TIME);
+                }
+            }
+        }
+
+        if self.is_connected() {
+            return;
+        }
+
+        let now = Instant::now();
+        let settling = self.settle_until.is_some_and(|until| now < until);
+        let overdue = self.unplugged
+            && now.duration_since(self.last_reopen) >= self.reconnect_policy.max_delay;
+        if settling || overdue {
+            self.last_reopen = now;
+            if let Err(e) = self.open_matching() {
+                debug!("Display not back yet: {}", e);
+            }
+        }
+    }
+
+    /// Reconnect to the device, retrying with backoff until it succeeds or
+    /// the policy's attempt limit is reached
+    pub fn reconnect_with_backoff(&mut self) -> Result<()> {
+        info!("Attempting to reconnect to device...");
+        self.device = None;
+        self.connect_with_backoff(self.reconnect_policy.max_attempts)
+    }
+
+    /// Connect, retrying with backoff for as long as the device is missing
+    pub fn wait_for_device(&mut self) -> Result<()> {
+        self.connect_with_backoff(None)
+    }
+
+    fn connect_with_backoff(&mut self, max_attempts: Option<u32>) -> Result<()> {
+        let mut backoff = Backoff::new(self.reconnect_policy.clone());
+        let mut attempts = 0;
+
+        loop {
+            let err = match self.connect() {
+                Ok(()) => return Ok(()),
+                Err(e) => e,
+            };
+
+            attempts += 1;
+            if max_attempts.is_some_and(|max| attempts >= max) {
+                return Err(OcypusError::Device(format!(
+                    "Giving up after {} connection attempts: {}",
+                    attempts, err
+                )));
+            }
+
+            let delay = backoff.next_delay();
+            warn!(
+                "{}; retrying in {:.1}s (attempt {})",
+                err,
+                delay.as_secs_f32(),
+                attempts + 1
+            );
+            thread::sleep(delay);
+        }
+    }
 }
 
 /// Build temperature report for the device
-fn build_temperature_report(
+pub fn build_temperature_report(
     temp_celsius: f32,
     unit: crate::config::TemperatureUnit,
 ) -> Result<[u8; REPORT_LENGTH]> {
-    let mut report = [0u8; REPORT_LENGTH];
-    report[0] = REPORT_ID;
+    build_report(Measurement::celsius(temp_celsius), unit)
+}
 
-    // Convert temperature based on unit
-    let display_temp = match unit {
-        crate::config::TemperatureUnit::Celsius => temp_celsius,
-        crate::config::TemperatureUnit::Fahrenheit => temp_celsius * 9.0 / 5.0 + 32.0,
-    };
+/// Build a report showing any metric. Temperatures are converted to `unit`,
+/// fan speeds are shown in tens of RPM, and load and power are shown as-is.
+/// Fractions are truncated.
+pub fn build_report(
+    measurement: Measurement,
+    unit: crate::config::TemperatureUnit,
+) -> Result<[u8; REPORT_LENGTH]> {
+    Ok(display_report(measurement, unit, &ReadoutConfig::default()).encode())
+}
 
-    // Clamp temperature to 0-999°C (or equivalent in F)
-    let clamped_temp = display_temp.clamp(0.0, 999.0);
-    let temp_int = clamped_temp as u32;
+/// The report showing `measurement` laid out as `readout` asks, for callers
+/// that adjust it further.
+///
+/// In decimal mode the digits show tenths with the decimal point flags set;
+/// values that don't fit (100 and above, or below 0) and configurations
+/// without decimal point flags fall back to whole numbers.
+pub fn display_report(
+    measurement: Measurement,
+    unit: crate::config::TemperatureUnit,
+    readout: &ReadoutConfig,
+) -> Report {
+    let display_value = match measurement.unit {
+        MetricUnit::Rpm => measurement.value / RPM_PER_UNIT,
+        _ => measurement.display_value(unit),
+    };
 
-    // Extract digits (hundreds, tens, ones)
-    report[1] = 0xff;
-    report[2] = 0xff;
-    report[3] = (temp_int / 100) as u8;
-    report[4] = ((temp_int / 10) % 10) as u8;
-    report[5] = (temp_int % 10) as u8;
+    let decimal = readout.decimal_point().and_then(|flags| {
+        let tenths = readout.rounding.apply(display_value * 10.0);
+        if tenths < 0.0 {
+            return None;
+        }
+        Report::with_tenths(tenths as u32, flags)
+    });
+    let report = decimal.unwrap_or_else(|| {
+        // Clamp to what the digits can show
+        let rounded = readout.rounding.apply(display_value);
+        Report::with_value(rounded.clamp(0.0, MAX_VALUE as f32) as u32)
+    });
 
     debug!(
-        "Built report: {:.1}°{} -> {}{}{}",
-        display_temp,
-        unit.as_char(),
-        report[3],
-        report[4],
-        report[5]
+        "Built report: {:.1}{} -> {:?}",
+        display_value,
+        measurement.unit.suffix(unit),
+        report.digits
     );
 
-    Ok(report)
+    report
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{Rounding, TemperatureUnit, REPORT_ID};
+    use crate::protocol::DEFAULT_FLAGS;
+    use crate::sensor::MetricUnit;
+    use crate::transport::MockTransport;
+    use std::sync::{Arc, Mutex};
+
+    /// Hotplug source returning whatever events were pushed since the last poll
+    #[derive(Clone, Default)]
+    struct FakeHotplug(Arc<Mutex<Vec<HotplugEvent>>>);
+
+    impl FakeHotplug {
+        fn push(&self, event: HotplugEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    impl HotplugSource for FakeHotplug {
+        fn poll(&mut self) -> Vec<HotplugEvent> {
+            std::mem::take(&mut *self.0.lock().unwrap())
+        }
+    }
+
+    fn device(path: &str, vendor_id: u16, product_id: u16, serial: Option<&str>) -> DeviceInfo {
+        DeviceInfo {
+            path: path.to_string(),
+            vendor_id,
+            product_id,
+            serial: serial.map(str::to_string),
+            manufacturer: Some("Ocypus".to_string()),
+            product: Some("Iota L24".to_string()),
+            interface_number: 0,
+            release_number: 0x0100,
+        }
+    }
+
+    #[test]
+    fn test_list_devices_filters_vid_pid() {
+        let mut transport = MockTransport::new();
+        transport.plug(device("/dev/hidraw0", 0x046d, 0xc52b, None));
+        transport.plug(device("/dev/hidraw3", VID, PID, Some("A")));
+        transport.plug(device("/dev/hidraw4", VID, 0x0001, Some("B")));
+        transport.plug(device("/dev/hidraw7", VID, PID, Some("C")));
+
+        let devices = list_devices(&mut transport).unwrap();
+        let paths: Vec<&str> = devices.iter().map(|d| d.path.as_str()).collect();
+        assert_eq!(paths, vec!["/dev/hidraw3", "/dev/hidraw7"]);
+    }
+
+    #[test]
+    fn test_device_info_matches() {
+        let info = device("/dev/hidraw3", VID, PID, Some("ABC123"));
+        assert!(info.matches(&DeviceSelector::Serial("ABC123".to_string())));
+        assert!(info.matches(&DeviceSelector::Path("/dev/hidraw3".to_string())));
+        assert!(!info.matches(&DeviceSelector::Serial("XYZ".to_string())));
+
+        let no_serial = DeviceInfo {
+            serial: None,
+            ..info
+        };
+        assert!(!no_serial.matches(&DeviceSelector::Serial(String::new())));
+    }
+
+    #[test]
+    fn test_connect_by_selector_and_send() {
+        let transport = MockTransport::new();
+        transport.plug(MockTransport::ocypus_device("/dev/hidraw3", Some("A")));
+        transport.plug(MockTransport::ocypus_device("/dev/hidraw7", Some("B")));
+
+        let selector = DeviceSelector::Serial("B".to_string());
+        let mut manager =
+            DeviceManager::with_transport(Box::new(transport.clone()), Some(selector));
+        assert!(manager
+            .send_temperature(40.0, TemperatureUnit::Celsius)
+            .is_err());
+
+        manager.connect().unwrap();
+        manager
+            .send_temperature(42.0, TemperatureUnit::Celsius)
+            .unwrap();
+
+        let reports = transport.reports_for("/dev/hidraw7");
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].len(), REPORT_LENGTH);
+        assert_eq!(&reports[0][3..6], &[0, 4, 2]);
+        assert!(transport.reports_for("/dev/hidraw3").is_empty());
+    }
+
+    #[test]
+    fn test_connect_without_matching_device() {
+        let transport = MockTransport::new();
+        transport.plug(MockTransport::ocypus_device("/dev/hidraw3", Some("A")));
+
+        let selector = DeviceSelector::Serial("Z".to_string());
+        let mut manager = DeviceManager::with_transport(Box::new(transport), Some(selector));
+        let err = manager.connect().unwrap_err().to_string();
+        assert!(
+            err.contains("matching serial:Z"),
+            "unexpected error: {}",
+            err
+        );
+        assert!(!manager.is_connected());
+    }
+
+    #[test]
+    fn test_reconnect_after_unplug() {
+        let transport = MockTransport::new();
+        let device = MockTransport::ocypus_device("/dev/hidraw3", None);
+        transport.plug(device.clone());
+
+        let mut manager = DeviceManager::with_transport(Box::new(transport.clone()), None);
+        manager.connect().unwrap();
+
+        transport.unplug("/dev/hidraw3");
+        assert!(manager
+            .send_temperature(50.0, TemperatureUnit::Celsius)
+            .is_err());
+        assert!(manager.reconnect().is_err());
+
+        transport.plug(device);
+        manager.reconnect().unwrap();
+        manager
+            .send_temperature(50.0, TemperatureUnit::Celsius)
+            .unwrap();
+        assert_eq!(transport.reports().len(), 1);
+        assert_eq!(transport.open_count(), 2);
+    }
+
+    fn quick_policy(max_attempts: Option<u32>) -> ReconnectPolicy {
+        ReconnectPolicy {
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            jitter: 0.0,
+            max_attempts,
+        }
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let mut backoff = Backoff::new(ReconnectPolicy {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+            jitter: 0.0,
+            max_attempts: None,
+        });
+        let delays: Vec<u128> = (0..6).map(|_| backoff.next_delay().as_millis()).collect();
+        assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+    }
+
+    #[test]
+    fn test_backoff_jitter_stays_in_range() {
+        let policy = ReconnectPolicy {
+            initial_delay: Duration::from_secs(8),
+            max_delay: Duration::from_secs(8),
+            jitter: 0.5,
+            max_attempts: None,
+        };
+        let mut backoff = Backoff::new(policy);
+        let delays: Vec<Duration> = (0..50).map(|_| backoff.next_delay()).collect();
+
+        assert!(delays
+            .iter()
+            .all(|d| *d > Duration::from_secs(4) && *d <= Duration::from_secs(8)));
+        assert!(delays.iter().any(|d| *d != delays[0]));
+    }
+
+    #[test]
+    fn test_reconnect_gives_up_after_max_attempts() {
+        let transport = MockTransport::new();
+        transport.plug(MockTransport::ocypus_device("/dev/hidraw3", None));
+
+        let mut manager = DeviceManager::with_transport(Box::new(transport.clone()), None);
+        manager.set_reconnect_policy(quick_policy(Some(3)));
+        manager.connect().unwrap();
+
+        transport.unplug("/dev/hidraw3");
+        let err = manager.reconnect_with_backoff().unwrap_err().to_string();
+        assert!(
+            err.contains("after 3 connection attempts"),
+            "unexpected error: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_wait_for_device() {
+        let transport = MockTransport::new();
+        let mut manager = DeviceManager::with_transport(Box::new(transport.clone()), None);
+        // The attempt limit only applies to reconnection
+        manager.set_reconnect_policy(quick_policy(Some(1)));
+
+        let plugger = transport.clone();
+        let handle = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            plugger.plug(MockTransport::ocypus_device("/dev/hidraw3", None));
+        });
+
+        manager.wait_for_device().unwrap();
+        assert!(manager.is_connected());
+        handle.join().unwrap();
+    }
+
+    #[test]
+    fn test_hotplug_unplug_and_replug() {
+        let transport = MockTransport::new();
+        let device = MockTransport::ocypus_device("/dev/hidraw3", None);
+        transport.plug(device.clone());
+        let hotplug = FakeHotplug::default();
+
+        let mut manager = DeviceManager::with_transport(Box::new(transport.clone()), None);
+        manager.set_hotplug(Box::new(hotplug.clone()));
+        manager.connect().unwrap();
+
+        // Another hidraw node going away is ignored
+        hotplug.push(HotplugEvent::Removed("/dev/hidraw0".to_string()));
+        manager.poll_hotplug();
+        assert!(manager.is_connected());
+
+        transport.unplug("/dev/hidraw3");
+        hotplug.push(HotplugEvent::Removed("/dev/hidraw3".to_string()));
+        manager.poll_hotplug();
+        assert!(manager.is_unplugged());
+        assert!(!manager.is_connected());
+
+        transport.plug(device);
+        hotplug.push(HotplugEvent::Added("/dev/hidraw3".to_string()));
+        manager.poll_hotplug();
+        assert!(manager.is_connected());
+        assert!(!manager.is_unplugged());
+        manager
+            .send_temperature(30.0, TemperatureUnit::Celsius)
+            .unwrap();
+        assert_eq!(transport.reports().len(), 1);
+    }
+
+    #[test]
+    fn test_hotplug_resume_reopens() {
+        let transport = MockTransport::new();
+        transport.plug(MockTransport::ocypus_device("/dev/hidraw3", None));
+        let hotplug = FakeHotplug::default();
+
+        let mut manager = DeviceManager::with_transport(Box::new(transport.clone()), None);
+        manager.set_hotplug(Box::new(hotplug.clone()));
+        manager.connect().unwrap();
+
+        hotplug.push(HotplugEvent::Resumed);
+        manager.poll_hotplug();
+        assert!(manager.is_connected());
+        assert_eq!(transport.open_count(), 2);
+    }
+
+    #[test]
+    fn test_read_reports() {
+        let transport = MockTransport::new();
+        transport.plug(MockTransport::ocypus_device("/dev/hidraw3", Some("A")));
+        transport.set_feature_report(vec![0x07, 0x01, 0x02]);
+        transport.set_input_report(vec![0x07, 0xaa]);
+        transport.set_report_descriptor(vec![0x06, 0x00, 0xff]);
+        transport.push_input(vec![0x07, 0x00, 0x01]);
+
+        let mut manager = DeviceManager::with_transport(Box::new(transport), None);
+        assert!(manager.get_feature_report(0x07).is_err());
+
+        manager.connect().unwrap();
+        assert_eq!(manager.connected_device().unwrap().path, "/dev/hidraw3");
+        assert_eq!(
+            manager.get_feature_report(0x07).unwrap(),
+            vec![0x07, 0x01, 0x02]
+        );
+        assert_eq!(manager.get_input_report(0x07).unwrap(), vec![0x07, 0xaa]);
+        assert_eq!(manager.report_descriptor().unwrap(), vec![0x06, 0x00, 0xff]);
+        assert!(manager.get_input_report(0x01).is_err());
+
+        let timeout = Duration::from_millis(10);
+        assert_eq!(
+            manager.read_report(timeout).unwrap(),
+            Some(vec![0x07, 0x00, 0x01])
+        );
+        assert_eq!(manager.read_report(timeout).unwrap(), None);
+    }
+
+    #[test]
+    fn test_send_raw() {
+        let transport = MockTransport::new();
+        transport.plug(MockTransport::ocypus_device("/dev/hidraw3", None));
+        let mut manager = DeviceManager::with_transport(Box::new(transport.clone()), None);
+
+        let mut report = [0u8; REPORT_LENGTH];
+        report[..3].copy_from_slice(&[REPORT_ID, 0x12, 0x34]);
+        assert!(manager.send_raw(&report).is_err());
+
+        manager.connect().unwrap();
+        manager.send_raw(&report).unwrap();
+        assert_eq!(transport.reports(), vec![report.to_vec()]);
+    }
 
     #[test]
     fn test_build_temperature_report_celsius() {
@@ -166,4 +812,86 @@ mod tests {
         assert_eq!(report[4], 9);
         assert_eq!(report[5], 9);
     }
+
+    #[test]
+    fn test_build_report_skips_conversion_for_other_metrics() {
+        let load = Measurement::new(25.0, MetricUnit::Percent);
+        let report = build_report(load, TemperatureUnit::Fahrenheit).unwrap();
+        assert_eq!(&report[3..6], &[0, 2, 5]);
+
+        let power = Measurement::new(187.6, MetricUnit::Watts);
+        let report = build_report(power, TemperatureUnit::Fahrenheit).unwrap();
+        assert_eq!(&report[3..6], &[1, 8, 7]);
+
+        // Fans read in tens of RPM; only implausibly fast ones are clamped
+        let fan = Measurement::new(1500.0, MetricUnit::Rpm);
+        let report = build_report(fan, TemperatureUnit::Celsius).unwrap();
+        assert_eq!(&report[3..6], &[1, 5, 0]);
+        let fan = Measurement::new(1458.0, MetricUnit::Rpm);
+        let report = build_report(fan, TemperatureUnit::Fahrenheit).unwrap();
+        assert_eq!(&report[3..6], &[1, 4, 5]);
+        let fan = Measurement::new(12000.0, MetricUnit::Rpm);
+        let report = build_report(fan, TemperatureUnit::Celsius).unwrap();
+        assert_eq!(&report[3..6], &[9, 9, 9]);
+    }
+
+    #[test]
+    fn test_display_report_rounding() {
+        let shown = |celsius: f32, rounding: Rounding| {
+            let readout = ReadoutConfig {
+                rounding,
+                ..ReadoutConfig::default()
+            };
+            display_report(
+                Measurement::celsius(celsius),
+                TemperatureUnit::Celsius,
+                &readout,
+            )
+            .value()
+        };
+        assert_eq!(shown(45.9, Rounding::Truncate), Some(45));
+        assert_eq!(shown(45.9, Rounding::Round), Some(46));
+        assert_eq!(shown(45.4, Rounding::Round), Some(45));
+        assert_eq!(shown(45.1, Rounding::Ceil), Some(46));
+        assert_eq!(shown(999.5, Rounding::Ceil), Some(999));
+        assert_eq!(shown(-0.4, Rounding::Round), Some(0));
+    }
+
+    #[test]
+    fn test_display_report_decimal() {
+        const POINT: [u8; 2] = [0xff, 0x7f];
+        let readout = ReadoutConfig {
+            rounding: Rounding::Round,
+            decimal: true,
+            decimal_flags: Some(POINT),
+        };
+        let shown = |value: f32, readout: &ReadoutConfig| {
+            let report = display_report(
+                Measurement::celsius(value),
+                TemperatureUnit::Celsius,
+                readout,
+            );
+            (report.flags, report.digits)
+        };
+
+        assert_eq!(shown(45.9, &readout), (POINT, [4, 5, 9]));
+        assert_eq!(shown(45.96, &readout), (POINT, [4, 6, 0]));
+        assert_eq!(shown(7.25, &readout), (POINT, [0, 7, 3]));
+        // Too wide for one decimal place: whole number, plain flags
+        assert_eq!(shown(104.6, &readout), (DEFAULT_FLAGS, [1, 0, 5]));
+        assert_eq!(shown(99.96, &readout), (DEFAULT_FLAGS, [1, 0, 0]));
+        assert_eq!(shown(-3.0, &readout), (DEFAULT_FLAGS, [0, 0, 0]));
+
+        // Without decimal point flags the setting has no effect
+        let no_flags = ReadoutConfig {
+            decimal_flags: None,
+            ..readout
+        };
+        assert_eq!(shown(45.9, &no_flags), (DEFAULT_FLAGS, [0, 4, 6]));
+        let off = ReadoutConfig {
+            decimal: false,
+            ..readout
+        };
+        assert_eq!(shown(45.9, &off), (DEFAULT_FLAGS, [0, 4, 6]));
+    }
 }
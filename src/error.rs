@@ -19,18 +19,42 @@ pub enum OcypusError {
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 
+    /// JSON output errors
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] serde_json::Error),
+
     /// HID API errors
     #[error("HID error: {0}")]
     HidApi(String),
 
+    /// Malformed display reports
+    #[error("Protocol error: {0}")]
+    Protocol(String),
+
     /// Temperature parsing errors
     #[error("Failed to parse temperature: {0}")]
     TemperatureParse(String),
 
     /// Invalid sensor type
-    #[error("Invalid sensor type: '{0}'. Supported types: cpu, gpu")]
-    InvalidSensorType(String),
+    #[error("Invalid sensor type: '{0}'. {1}")]
+    InvalidSensorType(String, String),
+}
+
+impl OcypusError {
+    /// The message without the category prefix of `Display`, for wrapping
+    /// in another error without repeating "Configuration error: " at every
+    /// level
+    pub fn message(&self) -> String {
+        match self {
+            OcypusError::Device(message)
+            | OcypusError::Sensor(message)
+            | OcypusError::Config(message)
+            | OcypusError::HidApi(message)
+            | OcypusError::Protocol(message) => message.clone(),
+            other => other.to_string(),
+        }
+    }
 }
 
 /// Result type alias for convenience
-pub type Result<T> = std::result::Result<T, OcypusError>;
\ No newline at end of file
+pub type Result<T> = std::result::Result<T, OcypusError>;
@@ -0,0 +1,206 @@
+//! Smoothing applied to readings before they reach the display.
+//!
+//! Filters run in a fixed order: the rolling median drops single-sample
+//! spikes, the moving average evens out noise, and the hysteresis band keeps
+//! the shown value from flickering between two digits.
+
+use crate::config::FilterConfig;
+use std::collections::VecDeque;
+
+/// A stateful stage transforming a series of readings
+pub trait Filter: Send {
+    /// Feed the next reading and return the filtered value
+    fn apply(&mut self, value: f32) -> f32;
+}
+
+/// Exponential moving average; `alpha` is the weight of the newest reading
+#[derive(Debug, Clone)]
+pub struct Ema {
+    alpha: f32,
+    average: Option<f32>,
+}
+
+impl Ema {
+    pub fn new(alpha: f32) -> Self {
+        Self {
+            alpha,
+            average: None,
+        }
+    }
+}
+
+impl Filter for Ema {
+    fn apply(&mut self, value: f32) -> f32 {
+        let average = match self.average {
+            Some(average) => average + self.alpha * (value - average),
+            None => value,
+        };
+        self.average = Some(average);
+        average
+    }
+}
+
+/// Median of the last `window` readings
+#[derive(Debug, Clone)]
+pub struct RollingMedian {
+    window: usize,
+    values: VecDeque<f32>,
+}
+
+impl RollingMedian {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            values: VecDeque::with_capacity(window),
+        }
+    }
+}
+
+impl Filter for RollingMedian {
+    fn apply(&mut self, value: f32) -> f32 {
+        if self.values.len() == self.window {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+
+        let mut sorted: Vec<f32> = self.values.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+        let mid = sorted.len() / 2;
+        if sorted.len().is_multiple_of(2) {
+            (sorted[mid - 1] + sorted[mid]) / 2.0
+        } else {
+            sorted[mid]
+        }
+    }
+}
+
+/// Holds the last passed value until a reading moves more than `band` away
+/// from it
+#[derive(Debug, Clone)]
+pub struct Hysteresis {
+    band: f32,
+    shown: Option<f32>,
+}
+
+impl Hysteresis {
+    pub fn new(band: f32) -> Self {
+        Self { band, shown: None }
+    }
+}
+
+impl Filter for Hysteresis {
+    fn apply(&mut self, value: f32) -> f32 {
+        match self.shown {
+            Some(shown) if (value - shown).abs() <= self.band => shown,
+            _ => {
+                self.shown = Some(value);
+                value
+            }
+        }
+    }
+}
+
+/// The filters enabled in a `FilterConfig`, applied in order
+#[derive(Default)]
+pub struct FilterChain {
+    filters: Vec<Box<dyn Filter>>,
+}
+
+impl FilterChain {
+    /// Build the chain, leaving out stages whose settings are no-ops
+    pub fn new(config: &FilterConfig) -> Self {
+        let mut filters: Vec<Box<dyn Filter>> = Vec::new();
+        if config.median_window > 1 {
+            filters.push(Box::new(RollingMedian::new(config.median_window)));
+        }
+        if config.ema_alpha < 1.0 {
+            filters.push(Box::new(Ema::new(config.ema_alpha)));
+        }
+        if config.hysteresis > 0.0 {
+            filters.push(Box::new(Hysteresis::new(config.hysteresis)));
+        }
+        Self { filters }
+    }
+
+    /// Whether the chain passes readings through unchanged
+    pub fn is_empty(&self) -> bool {
+        self.filters.is_empty()
+    }
+}
+
+impl Filter for FilterChain {
+    fn apply(&mut self, value: f32) -> f32 {
+        self.filters
+            .iter_mut()
+            .fold(value, |value, filter| filter.apply(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(filter: &mut dyn Filter, series: &[f32]) -> Vec<f32> {
+        series.iter().map(|value| filter.apply(*value)).collect()
+    }
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-4, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_ema() {
+        let out = run(&mut Ema::new(0.5), &[40.0, 50.0, 50.0, 30.0]);
+        assert_close(&out, &[40.0, 45.0, 47.5, 38.75]);
+
+        // A step is approached gradually, never overshot
+        let step: Vec<f32> = std::iter::once(0.0).chain([10.0; 20]).collect();
+        let out = run(&mut Ema::new(0.2), &step);
+        assert!(out.windows(2).all(|w| w[1] >= w[0] && w[1] <= 10.0));
+        assert!(out[20] > 9.8);
+    }
+
+    #[test]
+    fn test_rolling_median() {
+        // A single spike never makes it through a window of 3
+        let out = run(
+            &mut RollingMedian::new(3),
+            &[42.0, 42.0, 95.0, 42.0, 43.0, 43.0],
+        );
+        assert_close(&out, &[42.0, 42.0, 42.0, 42.0, 43.0, 43.0]);
+
+        // Even windows average the middle pair while filling up
+        let out = run(&mut RollingMedian::new(4), &[10.0, 20.0, 30.0, 40.0, 50.0]);
+        assert_close(&out, &[10.0, 15.0, 20.0, 25.0, 35.0]);
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        // Flicker around the 41/42 boundary is held; real moves pass
+        let series = [41.9, 42.1, 41.8, 42.2, 42.3, 43.0, 42.6, 41.9];
+        let out = run(&mut Hysteresis::new(0.5), &series);
+        assert_close(&out, &[41.9, 41.9, 41.9, 41.9, 41.9, 43.0, 43.0, 41.9]);
+    }
+
+    #[test]
+    fn test_chain() {
+        let config = FilterConfig::default();
+        let mut chain = FilterChain::new(&config);
+        assert!(chain.is_empty());
+        assert_close(&run(&mut chain, &[41.0, 95.0]), &[41.0, 95.0]);
+
+        let config = FilterConfig {
+            median_window: 3,
+            ema_alpha: 0.5,
+            hysteresis: 1.0,
+        };
+        let mut chain = FilterChain::new(&config);
+        // median: 40, 40, 40, 44, 44; EMA: 40, 40, 40, 42, 43;
+        // hysteresis holds 40 until the average moves past 41
+        let out = run(&mut chain, &[40.0, 40.0, 90.0, 44.0, 44.0]);
+        assert_close(&out, &[40.0, 40.0, 40.0, 42.0, 42.0]);
+    }
+}
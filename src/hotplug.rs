@@ -0,0 +1,402 @@
+use crate::error::Result;
+use log::{debug, info, warn};
+use std::collections::BTreeSet;
+use std::fs;
+use std::io;
+use std::mem;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Default sysfs hidraw class directory, polled when netlink is unavailable
+pub const DEFAULT_HIDRAW_ROOT: &str = "/sys/class/hidraw";
+
+/// Kernel uevent multicast group
+const KERNEL_UEVENT_GROUP: u32 = 1;
+
+/// How much longer the boot clock must have advanced than the monotonic
+/// clock before a gap counts as a suspend
+const RESUME_THRESHOLD: Duration = Duration::from_secs(2);
+
+/// A change to the set of hidraw devices
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HotplugEvent {
+    /// A hidraw node appeared (e.g. `/dev/hidraw3`)
+    Added(String),
+    /// A hidraw node went away
+    Removed(String),
+    /// The system resumed from suspend; open handles may be stale
+    Resumed,
+}
+
+/// Source of hotplug events, polled from the main loop
+pub trait HotplugSource: Send {
+    /// Return the events that happened since the last poll, without blocking
+    fn poll(&mut self) -> Vec<HotplugEvent>;
+}
+
+/// Parse a kernel uevent message (`add@/devices/...\0ACTION=add\0...`),
+/// returning an event for hidraw additions and removals
+pub fn parse_uevent(message: &[u8]) -> Option<HotplugEvent> {
+    let mut action = None;
+    let mut subsystem = None;
+    let mut devname = None;
+
+    // The first field is the `action@devpath` header; the rest are KEY=VALUE
+    for field in message.split(|b| *b == 0).skip(1) {
+        let field = std::str::from_utf8(field).ok()?;
+        match field.split_once('=') {
+            Some(("ACTION", value)) => action = Some(value),
+            Some(("SUBSYSTEM", value)) => subsystem = Some(value),
+            Some(("DEVNAME", value)) => devname = Some(value),
+            _ => {}
+        }
+    }
+
+    if subsystem? != "hidraw" {
+        return None;
+    }
+
+    let devname = devname?;
+    let path = if devname.starts_with('/') {
+        devname.to_string()
+    } else {
+        format!("/dev/{}", devname)
+    };
+
+    match action? {
+        "add" => Some(HotplugEvent::Added(path)),
+        "remove" => Some(HotplugEvent::Removed(path)),
+        _ => None,
+    }
+}
+
+/// Listens for kernel uevents on a netlink socket
+pub struct NetlinkListener {
+    socket: OwnedFd,
+}
+
+impl NetlinkListener {
+    /// Open a non-blocking socket subscribed to kernel uevents
+    pub fn new() -> Result<Self> {
+        // SAFETY: plain socket/bind calls; the descriptor is owned by
+        // `OwnedFd` as soon as it is valid
+        unsafe {
+            let fd = libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_DGRAM | libc::SOCK_CLOEXEC | libc::SOCK_NONBLOCK,
+                libc::NETLINK_KOBJECT_UEVENT,
+            );
+            if fd < 0 {
+                return Err(io::Error::last_os_error().into());
+            }
+            let socket = OwnedFd::from_raw_fd(fd);
+
+            let mut addr: libc::sockaddr_nl = mem::zeroed();
+            addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+            addr.nl_groups = KERNEL_UEVENT_GROUP;
+            let ret = libc::bind(
+                fd,
+                &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+                mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+            );
+            if ret < 0 {
+                return Err(io::Error::last_os_error().into());
+            }
+
+            Ok(Self { socket })
+        }
+    }
+}
+
+impl HotplugSource for NetlinkListener {
+    fn poll(&mut self) -> Vec<HotplugEvent> {
+        let mut events = Vec::new();
+        let mut buf = [0u8; 8192];
+
+        loop {
+            // SAFETY: `buf` is valid for writes of its full length
+            let len = unsafe {
+                libc::recv(
+                    self.socket.as_raw_fd(),
+                    buf.as_mut_ptr() as *mut libc::c_void,
+                    buf.len(),
+                    0,
+                )
+            };
+            if len < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() != io::ErrorKind::WouldBlock {
+                    debug!("Failed to read uevent: {}", err);
+                }
+                break;
+            }
+
+            events.extend(parse_uevent(&buf[..len as usize]));
+        }
+
+        events
+    }
+}
+
+/// Detects hidraw changes by listing `/sys/class/hidraw` on every poll
+pub struct HidrawPoller {
+    root: PathBuf,
+    known: BTreeSet<String>,
+}
+
+impl HidrawPoller {
+    /// Poll the default sysfs hidraw directory
+    pub fn new() -> Self {
+        Self::with_root(DEFAULT_HIDRAW_ROOT)
+    }
+
+    /// Poll a custom hidraw directory
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        let mut poller = Self {
+            root: root.into(),
+            known: BTreeSet::new(),
+        };
+        poller.known = poller.list();
+        poller
+    }
+
+    fn list(&self) -> BTreeSet<String> {
+        fs::read_dir(&self.root)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .filter(|name| name.starts_with("hidraw"))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl Default for HidrawPoller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HotplugSource for HidrawPoller {
+    fn poll(&mut self) -> Vec<HotplugEvent> {
+        let current = self.list();
+        let removed = self
+            .known
+            .difference(&current)
+            .map(|name| HotplugEvent::Removed(format!("/dev/{}", name)));
+        let added = current
+            .difference(&self.known)
+            .map(|name| HotplugEvent::Added(format!("/dev/{}", name)));
+        let events = removed.chain(added).collect();
+
+        self.known = current;
+        events
+    }
+}
+
+/// Notices suspend by comparing the monotonic clock, which stops while
+/// suspended, against the boot clock, which doesn't
+pub struct ResumeDetector {
+    monotonic: Instant,
+    boottime: Option<Duration>,
+}
+
+impl ResumeDetector {
+    pub fn new() -> Self {
+        Self {
+            monotonic: Instant::now(),
+            boottime: boottime(),
+        }
+    }
+
+    /// Check whether the system was suspended since the last check
+    pub fn resumed(&mut self) -> bool {
+        let monotonic = Instant::now();
+        let boottime = boottime();
+
+        let suspended = match (self.boottime, boottime) {
+            (Some(before), Some(after)) => suspended_for(
+                monotonic.duration_since(self.monotonic),
+                after.saturating_sub(before),
+            )
+            .is_some(),
+            _ => false,
+        };
+
+        self.monotonic = monotonic;
+        self.boottime = boottime;
+        suspended
+    }
+}
+
+impl Default for ResumeDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How long the system was suspended, given how far the monotonic and boot
+/// clocks advanced over the same interval
+fn suspended_for(monotonic: Duration, boottime: Duration) -> Option<Duration> {
+    let gap = boottime.saturating_sub(monotonic);
+    (gap > RESUME_THRESHOLD).then_some(gap)
+}
+
+/// Current `CLOCK_BOOTTIME`, which keeps counting during suspend
+fn boottime() -> Option<Duration> {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `ts` is a valid timespec to write into
+    if unsafe { libc::clock_gettime(libc::CLOCK_BOOTTIME, &mut ts) } != 0 {
+        return None;
+    }
+    Some(Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+}
+
+/// hidraw add/remove events from netlink (or sysfs polling when netlink is
+/// unavailable), plus resume-from-suspend notifications
+pub struct HotplugMonitor {
+    devices: Box<dyn HotplugSource>,
+    resume: ResumeDetector,
+}
+
+impl HotplugMonitor {
+    /// Listen on netlink, falling back to polling `/sys/class/hidraw`
+    pub fn new() -> Self {
+        let devices: Box<dyn HotplugSource> = match NetlinkListener::new() {
+            Ok(listener) => {
+                debug!("Listening for hidraw uevents on netlink");
+                Box::new(listener)
+            }
+            Err(e) => {
+                warn!(
+                    "Netlink uevents unavailable ({}); polling {}",
+                    e, DEFAULT_HIDRAW_ROOT
+                );
+                Box::new(HidrawPoller::new())
+            }
+        };
+
+        Self {
+            devices,
+            resume: ResumeDetector::new(),
+        }
+    }
+}
+
+impl Default for HotplugMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HotplugSource for HotplugMonitor {
+    fn poll(&mut self) -> Vec<HotplugEvent> {
+        let mut events = self.devices.poll();
+        if self.resume.resumed() {
+            info!("System resumed from suspend");
+            events.push(HotplugEvent::Resumed);
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn uevent(fields: &[&str]) -> Vec<u8> {
+        fields.join("\0").into_bytes()
+    }
+
+    #[test]
+    fn test_parse_hidraw_add() {
+        let message = uevent(&[
+            "add@/devices/pci0000:00/0000:00:14.0/usb1/1-4/1-4:1.0/0003:1A2C:434D.0007/hidraw/hidraw3",
+            "ACTION=add",
+            "DEVPATH=/devices/pci0000:00/0000:00:14.0/usb1/1-4/1-4:1.0/0003:1A2C:434D.0007/hidraw/hidraw3",
+            "SUBSYSTEM=hidraw",
+            "MAJOR=241",
+            "MINOR=3",
+            "DEVNAME=hidraw3",
+            "SEQNUM=4521",
+            "",
+        ]);
+        assert_eq!(
+            parse_uevent(&message),
+            Some(HotplugEvent::Added("/dev/hidraw3".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_hidraw_remove() {
+        let message = uevent(&[
+            "remove@/devices/virtual/hidraw/hidraw5",
+            "ACTION=remove",
+            "SUBSYSTEM=hidraw",
+            "DEVNAME=/dev/hidraw5",
+        ]);
+        assert_eq!(
+            parse_uevent(&message),
+            Some(HotplugEvent::Removed("/dev/hidraw5".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_ignores_other_uevents() {
+        let usb = uevent(&[
+            "add@/devices/pci0000:00/0000:00:14.0/usb1/1-4",
+            "ACTION=add",
+            "SUBSYSTEM=usb",
+            "DEVNAME=bus/usb/001/007",
+        ]);
+        assert_eq!(parse_uevent(&usb), None);
+
+        let change = uevent(&[
+            "change@/devices/virtual/hidraw/hidraw5",
+            "ACTION=change",
+            "SUBSYSTEM=hidraw",
+            "DEVNAME=hidraw5",
+        ]);
+        assert_eq!(parse_uevent(&change), None);
+
+        assert_eq!(parse_uevent(b"libudev\0\xfe\xed\xca\xfe"), None);
+        assert_eq!(parse_uevent(b""), None);
+    }
+
+    #[test]
+    fn test_hidraw_poller() {
+        let root = TempDir::new().unwrap();
+        fs::create_dir(root.path().join("hidraw0")).unwrap();
+        fs::create_dir(root.path().join("hidraw3")).unwrap();
+
+        let mut poller = HidrawPoller::with_root(root.path());
+        assert!(poller.poll().is_empty());
+
+        fs::remove_dir(root.path().join("hidraw3")).unwrap();
+        fs::create_dir(root.path().join("hidraw4")).unwrap();
+        assert_eq!(
+            poller.poll(),
+            vec![
+                HotplugEvent::Removed("/dev/hidraw3".to_string()),
+                HotplugEvent::Added("/dev/hidraw4".to_string()),
+            ]
+        );
+        assert!(poller.poll().is_empty());
+    }
+
+    #[test]
+    fn test_suspend_detection() {
+        let secs = Duration::from_secs;
+        assert_eq!(suspended_for(secs(1), secs(1)), None);
+        assert_eq!(suspended_for(secs(1), secs(2)), None);
+        assert_eq!(suspended_for(secs(1), secs(601)), Some(secs(600)));
+        assert!(!ResumeDetector::new().resumed());
+    }
+}
@@ -0,0 +1,40 @@
+//! Library for driving the Ocypus Iota L24 digital display.
+//!
+//! The `ocypus-l24-digital` binary is a thin consumer of this crate; other
+//! tools can use the same building blocks to read sensors and drive the
+//! display directly:
+//!
+//! ```no_run
+//! use ocypus_l24_digital::{Config, DeviceManager, TemperatureMonitor};
+//!
+//! # fn main() -> ocypus_l24_digital::Result<()> {
+//! let config = Config::default().validate()?;
+//! let mut device = DeviceManager::new()?;
+//! device.connect()?;
+//!
+//! let monitor = TemperatureMonitor::new(config);
+//! let temp = monitor.get_current_temperature()?;
+//! device.send_temperature(temp, monitor.config().temperature_unit)?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod config;
+pub mod device;
+pub mod error;
+pub mod filter;
+pub mod hotplug;
+pub mod monitor;
+pub mod probe;
+pub mod protocol;
+pub mod reload;
+pub mod runner;
+pub mod sensor;
+pub mod transport;
+
+pub use config::{Config, SensorType, TemperatureUnit};
+pub use device::{build_report, build_temperature_report, DeviceManager};
+pub use error::{OcypusError, Result};
+pub use monitor::{Sample, SensorManager, TemperatureMonitor};
+pub use protocol::Report;
+pub use sensor::{Measurement, MetricUnit, TemperatureSource};
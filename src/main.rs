@@ -1,17 +1,19 @@
-mod config;
-mod device;
-mod error;
-mod monitor;
-mod sensor;
-
 use clap::Parser;
-use config::{Args, Config};
-use device::DeviceManager;
-use error::Result;
-use log::{error, info};
-use monitor::TemperatureMonitor;
+use log::{error, info, warn};
+use ocypus_l24_digital::config::{Args, Command, Config, DeviceSelector, REPORT_LENGTH};
+use ocypus_l24_digital::device::DeviceInfo;
+use ocypus_l24_digital::hotplug::HotplugMonitor;
+use ocypus_l24_digital::reload::ConfigReloader;
+use ocypus_l24_digital::runner::main_loop;
+use ocypus_l24_digital::sensor::thermal_zone::ThermalZoneSensor;
+use ocypus_l24_digital::{probe, protocol};
+use ocypus_l24_digital::{
+    DeviceManager, OcypusError, Result, SensorManager, TemperatureMonitor, TemperatureUnit,
+};
+use std::fs;
+use std::path::Path;
 use std::process;
-use std::sync::mpsc;
+use std::str::FromStr;
 use std::thread;
 use std::time::Duration;
 
@@ -22,25 +24,59 @@ fn main() {
     // Parse command line arguments
     let args = Args::parse();
 
-    // Create and validate configuration
-    let config = match Config::from_args(&args).and_then(|c| c.validate()) {
-        Ok(config) => config,
+    if let Some(command) = &args.command {
+        if let Err(e) = run_command(&args, command) {
+            error!("{}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if args.list_zones {
+        if let Err(e) = list_thermal_zones() {
+            error!("Failed to list thermal zones: {}", e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    // Create and validate one configuration per display, against the
+    // sources the displays will be monitored with
+    let sensors = SensorManager::new();
+    let displays = match Config::load_displays(&args).and_then(|displays| {
+        displays
+            .into_iter()
+            .map(|config| validate(config, &sensors))
+            .collect::<Result<Vec<_>>>()
+    }) {
+        Ok(displays) => displays,
         Err(e) => {
-            error!("Configuration error: {}", e);
+            error!("Configuration error: {}", e.message());
             process::exit(1);
         }
     };
 
     // Print configuration
-    print_config(&config);
+    info!("ocypus-digital v{}", env!("CARGO_PKG_VERSION"));
+    for config in &displays {
+        print_config(config);
+    }
 
     // Run the application
-    if let Err(e) = run_application(&config) {
+    if let Err(e) = run_application(&args, displays, sensors) {
         error!("Application error: {}", e);
         process::exit(1);
     }
 }
 
+/// Validate a display's configuration and check that its sensors name
+/// sources in `sensors`, before any device is opened
+fn validate(config: Config, sensors: &SensorManager) -> Result<Config> {
+    let config = config.validate()?;
+    sensors.check_names(&config)?;
+    Ok(config)
+}
+
 /// Setup logging based on configuration
 fn setup_logging() {
     let args = Args::parse();
@@ -59,7 +95,12 @@ fn setup_logging() {
 
 /// Print the current configuration
 fn print_config(config: &Config) {
-    info!("ocypus-digital v{}", env!("CARGO_PKG_VERSION"));
+    if let Some(device) = &config.device {
+        info!("Display {}:", device);
+    }
+    if let Some(path) = &config.source {
+        info!("Loaded configuration from {}", path.display());
+    }
     info!(
         "Using temperature unit: °{}",
         config.temperature_unit.as_char()
@@ -68,7 +109,21 @@ fn print_config(config: &Config) {
         "Update interval: {} seconds",
         config.update_interval.as_secs()
     );
-    info!("Using sensor: {}", config.sensor_type.as_str());
+    if config.rotation.is_empty() {
+        info!("Using sensor: {}", config.sensor_type);
+    } else {
+        let entries: Vec<String> = config
+            .rotation
+            .iter()
+            .map(|e| format!("{} ({}s)", e.sensor, e.dwell.as_secs()))
+            .collect();
+        info!("Rotating through: {}", entries.join(", "));
+    }
+    if config.readout.decimal && config.readout.decimal_point().is_none() {
+        warn!(
+            "Decimal display needs `decimal_flags` in the [readout] table; showing whole numbers"
+        );
+    }
 
     if config.alerts_enabled {
         info!(
@@ -78,80 +133,352 @@ fn print_config(config: &Config) {
     }
 }
 
-/// Main application logic
-fn run_application(config: &Config) -> Result<()> {
-    // Initialize HID API and device manager
-    let mut device_manager = DeviceManager::new()?;
+/// Run a subcommand
+fn run_command(args: &Args, command: &Command) -> Result<()> {
+    match command {
+        Command::ListDevices { json } => list_devices(*json),
+        Command::Sensors { json } => list_sensors(*json),
+        Command::DeviceInfo { json, timeout_ms } => {
+            device_info(args, *json, Duration::from_millis(*timeout_ms))
+        }
+        Command::SendRaw {
+            hex,
+            file,
+            binary,
+            read,
+            sweep,
+            delay_ms,
+        } => send_raw(
+            args,
+            hex.as_deref(),
+            file.as_deref(),
+            *binary,
+            read.map(Duration::from_millis),
+            *sweep,
+            Duration::from_millis(*delay_ms),
+        ),
+    }
+}
 
-    // Connect to the device
+/// Connect to the display selected by `--device`
+fn connect_selected(args: &Args) -> Result<DeviceManager> {
+    let selector = args
+        .device
+        .as_deref()
+        .map(DeviceSelector::from_str)
+        .transpose()?;
+    let mut device_manager = DeviceManager::with_selector(selector)?;
     device_manager.connect()?;
+    Ok(device_manager)
+}
 
-    // Initialize temperature monitor
-    let temperature_monitor = TemperatureMonitor::new(config.clone());
-
-    // Start temperature monitoring in a separate thread
-    let temp_receiver = temperature_monitor.start_monitoring()?;
+/// Read report bytes from a file of hex text, or its raw bytes with
+/// `--binary`. A file that isn't valid hex is an error rather than being
+/// sent as is.
+fn read_report_file(path: &Path, binary: bool) -> Result<Vec<u8>> {
+    let data = fs::read(path)?;
+    if binary {
+        return Ok(data);
+    }
 
-    // Main application loop
-    main_loop(&mut device_manager, &temperature_monitor, temp_receiver)
+    let text = std::str::from_utf8(&data).map_err(|_| {
+        OcypusError::Config(format!(
+            "{} is not hex text; pass --binary to send its raw bytes",
+            path.display()
+        ))
+    })?;
+    protocol::parse_hex(text).map_err(|e| {
+        OcypusError::Config(format!(
+            "{}: {}; pass --binary to send the file's raw bytes",
+            path.display(),
+            e
+        ))
+    })
 }
 
-/// Main application loop
-fn main_loop(
-    device_manager: &mut DeviceManager,
-    temperature_monitor: &TemperatureMonitor,
-    temp_receiver: mpsc::Receiver<f32>,
+/// Send a hand-written report, optionally sweeping one byte through every value
+fn send_raw(
+    args: &Args,
+    hex: Option<&str>,
+    file: Option<&Path>,
+    binary: bool,
+    read: Option<Duration>,
+    sweep: Option<usize>,
+    delay: Duration,
 ) -> Result<()> {
-    info!("Starting temperature monitoring loop");
-
-    for temp_celsius in temp_receiver {
-        match device_manager
-            .send_temperature(temp_celsius, temperature_monitor.config().temperature_unit)
-        {
-            Ok(_) => {
-                let display_temp = temperature_monitor.convert_temperature(temp_celsius);
-                info!(
-                    "Temperature: {:.0}°{}",
-                    display_temp,
-                    temperature_monitor.config().temperature_unit.as_char()
-                );
+    let bytes = match (hex, file) {
+        (Some(hex), _) => protocol::parse_hex(hex)?,
+        (None, Some(path)) => read_report_file(path, binary)?,
+        (None, None) => {
+            return Err(OcypusError::Config(
+                "either hex bytes or --file is required".to_string(),
+            ))
+        }
+    };
+    let report = protocol::pad_report(&bytes)?;
+
+    let mut device_manager = connect_selected(args)?;
+    let mut send = |label: String, report: &[u8; REPORT_LENGTH]| -> Result<()> {
+        println!(
+            "{}: {}",
+            label,
+            probe::to_hex(&report[..last_nonzero(report)])
+        );
+        device_manager.send_raw(report)?;
+
+        if let Some(timeout) = read {
+            match device_manager.read_report(timeout)? {
+                Some(response) => print!("{}", probe::hex_dump(&response)),
+                None => println!("  no response within {} ms", timeout.as_millis()),
+            }
+        }
+        Ok(())
+    };
+
+    match sweep {
+        Some(index) => {
+            for (value, report) in protocol::sweep(report, index)? {
+                send(format!("byte[{}] = {:#04x}", index, value), &report)?;
+                thread::sleep(delay);
             }
-            Err(e) => {
-                error!("Device communication error: {}", e);
-
-                // Attempt reconnection
-                info!("Attempting to reconnect...");
-                match device_manager.reconnect() {
-                    Ok(_) => {
-                        info!("Successfully reconnected to device");
-                        // Try to send the failed temperature again
-                        if let Err(retry_err) = device_manager.send_temperature(
-                            temp_celsius,
-                            temperature_monitor.config().temperature_unit,
-                        ) {
-                            error!(
-                                "Failed to send temperature after reconnection: {}",
-                                retry_err
-                            );
-                        }
-                    }
-                    Err(reconnect_err) => {
-                        error!("Failed to reconnect: {}", reconnect_err);
-                        // Wait before retrying
-                        thread::sleep(Duration::from_secs(5));
-                    }
+        }
+        None => send("sent".to_string(), &report)?,
+    }
+    Ok(())
+}
+
+/// Length of `report` up to its last non-zero byte (at least the report ID)
+fn last_nonzero(report: &[u8]) -> usize {
+    report.iter().rposition(|b| *b != 0).map_or(1, |i| i + 1)
+}
+
+/// Dump what the display reports about itself
+fn device_info(args: &Args, json: bool, timeout: Duration) -> Result<()> {
+    let mut device_manager = connect_selected(args)?;
+    let probe = probe::probe(&mut device_manager, timeout)?;
+
+    if json {
+        let text = serde_json::to_string_pretty(&probe)?;
+        println!("{}", text);
+        return Ok(());
+    }
+
+    let device = &probe.device;
+    println!("Device:        {}", device.path);
+    println!("Serial:        {}", device.serial.as_deref().unwrap_or("-"));
+    println!(
+        "Product:       {} {}",
+        device.manufacturer.as_deref().unwrap_or("-"),
+        device.product.as_deref().unwrap_or("-")
+    );
+    println!(
+        "Release:       {:x}.{:02x}",
+        device.release_number >> 8,
+        device.release_number & 0xff
+    );
+
+    match &probe.descriptor {
+        Some(descriptor) => {
+            println!("\nReport descriptor ({} bytes):", descriptor.len());
+            print!("{}", probe::hex_dump(descriptor));
+        }
+        None => println!("\nReport descriptor: unavailable"),
+    }
+    for layout in &probe.layouts {
+        println!(
+            "  {:<8} report {:#04x}: {} bytes",
+            format!("{:?}", layout.kind).to_lowercase(),
+            layout.report_id,
+            layout.length
+        );
+    }
+
+    for report in &probe.reports {
+        let source = format!("{:?}", report.source).to_lowercase();
+        println!("\n{} report {:#04x}:", source, report.report_id);
+        match (&report.data, &report.error) {
+            (Some(data), _) => {
+                print!("{}", probe::hex_dump(data));
+                for field in &report.fields {
+                    println!("  {:<10} {}", field.name, field.value);
                 }
             }
+            (None, Some(error)) => println!("  error: {}", error),
+            (None, None) => println!("  empty"),
         }
     }
+    if !probe
+        .reports
+        .iter()
+        .any(|r| r.source == probe::ReportSource::Interrupt)
+    {
+        println!(
+            "\nNo unsolicited input report within {} ms",
+            timeout.as_millis()
+        );
+    }
+    Ok(())
+}
+
+/// Print every discovered temperature reading
+fn list_sensors(json: bool) -> Result<()> {
+    let readings = SensorManager::new().discover();
 
+    if json {
+        let text = serde_json::to_string_pretty(&readings)?;
+        println!("{}", text);
+        return Ok(());
+    }
+
+    println!(
+        "{:<32} {:<11} {:<16} {:<20} {:>8}  DEFAULT",
+        "SENSOR", "BACKEND", "CHIP", "LABEL", "VALUE"
+    );
+    for reading in &readings {
+        println!(
+            "{:<32} {:<11} {:<16} {:<20} {:>8}  {}",
+            reading.sensor,
+            reading.backend,
+            reading.chip,
+            reading.label,
+            reading.value.map_or_else(
+                || "-".to_string(),
+                |v| format!("{:.1}{}", v, reading.unit.suffix(TemperatureUnit::Celsius))
+            ),
+            if reading.default { "*" } else { "" }
+        );
+    }
+    Ok(())
+}
+
+/// Print the devices matching the Ocypus VID/PID
+fn list_devices(json: bool) -> Result<()> {
+    let devices = DeviceManager::new()?.enumerate()?;
+
+    if json {
+        let text = serde_json::to_string_pretty(&devices)?;
+        println!("{}", text);
+        return Ok(());
+    }
+
+    if devices.is_empty() {
+        println!("No Ocypus Iota L24 devices found");
+        return Ok(());
+    }
+
+    println!(
+        "{:<16} {:<20} {:<16} {:<20} {:>9} {:>7}",
+        "PATH", "SERIAL", "MANUFACTURER", "PRODUCT", "INTERFACE", "RELEASE"
+    );
+    for device in &devices {
+        print_device(device);
+    }
     Ok(())
 }
 
+/// Print one row of the device table
+fn print_device(device: &DeviceInfo) {
+    println!(
+        "{:<16} {:<20} {:<16} {:<20} {:>9} {:>7}",
+        device.path,
+        device.serial.as_deref().unwrap_or("-"),
+        device.manufacturer.as_deref().unwrap_or("-"),
+        device.product.as_deref().unwrap_or("-"),
+        device.interface_number,
+        format!(
+            "{:x}.{:02x}",
+            device.release_number >> 8,
+            device.release_number & 0xff
+        ),
+    );
+}
+
+/// Print the available thermal zones
+fn list_thermal_zones() -> Result<()> {
+    for zone in ThermalZoneSensor::new(None).zones()? {
+        match zone.read_temperature() {
+            Ok(temp) => println!(
+                "thermal_zone{}\t{}\t{:.1}°C",
+                zone.index, zone.zone_type, temp
+            ),
+            Err(_) => println!("thermal_zone{}\t{}\t-", zone.index, zone.zone_type),
+        }
+    }
+    Ok(())
+}
+
+/// Main application logic: drive every configured display in its own thread
+fn run_application(args: &Args, displays: Vec<Config>, sensors: SensorManager) -> Result<()> {
+    let multiple = displays.len() > 1;
+    let mut shared_configs = Vec::with_capacity(displays.len());
+    let mut handles = Vec::with_capacity(displays.len());
+
+    for config in displays {
+        let label = config
+            .device
+            .as_ref()
+            .map_or_else(|| "default".to_string(), |d| d.to_string());
+
+        // Initialize temperature monitor
+        let temperature_monitor = TemperatureMonitor::with_sensor_manager(config, sensors.clone());
+        shared_configs.push(temperature_monitor.shared_config());
+
+        handles.push(thread::spawn(move || {
+            let result = run_display(&temperature_monitor);
+            if let Err(e) = &result {
+                if multiple {
+                    error!("Display {} stopped: {}", label, e);
+                }
+            }
+            result
+        }));
+    }
+
+    // Reload configuration on SIGHUP or configuration file change
+    ConfigReloader::new(args.clone(), shared_configs)
+        .with_sensors(sensors)
+        .spawn()?;
+
+    let mut first_error = None;
+    for handle in handles {
+        let result = handle
+            .join()
+            .unwrap_or_else(|_| Err(OcypusError::Device("display thread panicked".to_string())));
+        if let Err(e) = result {
+            first_error.get_or_insert(e);
+        }
+    }
+
+    first_error.map_or(Ok(()), Err)
+}
+
+/// Connect to one display and feed it readings from its monitor
+fn run_display(temperature_monitor: &TemperatureMonitor) -> Result<()> {
+    let config = temperature_monitor.config();
+
+    // Initialize HID API and device manager
+    let mut device_manager = DeviceManager::with_selector(config.device.clone())?;
+    device_manager.set_reconnect_policy(config.reconnect);
+    device_manager.set_hotplug(Box::new(HotplugMonitor::new()));
+
+    // Connect to the device, or wait for it to be plugged in
+    if config.wait_for_device {
+        device_manager.wait_for_device()?;
+    } else {
+        device_manager.connect()?;
+    }
+
+    // Start temperature monitoring in a separate thread
+    let temp_receiver = temperature_monitor.start_monitoring()?;
+
+    // Main application loop
+    main_loop(&mut device_manager, temperature_monitor, temp_receiver)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use config::TemperatureUnit;
+    use ocypus_l24_digital::{SensorType, TemperatureUnit};
 
     #[test]
     fn test_config_validation() {
@@ -159,21 +486,68 @@ mod tests {
         assert!(config.clone().validate().is_ok());
 
         // Test invalid thresholds
-        let mut config = Config::default();
-        config.high_threshold = 50.0;
-        config.low_threshold = 60.0;
+        let config = Config {
+            high_threshold: 50.0,
+            low_threshold: 60.0,
+            ..Config::default()
+        };
         assert!(config.validate().is_err());
 
         // Test invalid interval
-        let mut config = Config::default();
-        config.update_interval = Duration::from_secs(0);
+        let config = Config {
+            update_interval: Duration::from_secs(0),
+            ..Config::default()
+        };
         assert!(config.validate().is_err());
+
+        // Test unknown sensor names, on their own and inside an aggregate
+        let sensors = SensorManager::new();
+        for sensor in ["system", "max:cpu,system"] {
+            let config = Config {
+                sensor_type: SensorType::from_str(sensor).unwrap(),
+                ..Config::default()
+            };
+            assert!(validate(config, &sensors).is_err());
+        }
+    }
+
+    #[test]
+    fn test_sensor_type_parsing() {
+        assert_eq!(SensorType::from_str("CPU").unwrap(), SensorType::new("cpu"));
+
+        let thermal = SensorType::from_str("thermal:cpu-thermal").unwrap();
+        assert_eq!(thermal.name(), "thermal");
+        assert_eq!(thermal.selector(), Some("cpu-thermal"));
+        assert_eq!(thermal.to_string(), "thermal:cpu-thermal");
+
+        assert!(SensorType::from_str("thermal:").is_err());
+        assert!(SensorType::from_str(":x86_pkg_temp").is_err());
+    }
+
+    #[test]
+    fn test_read_report_file() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), "07 ff ff 00 04 02\n").unwrap();
+        assert_eq!(
+            read_report_file(file.path(), false).unwrap(),
+            vec![0x07, 0xff, 0xff, 0x00, 0x04, 0x02]
+        );
+
+        // A typo is an error, not a report made of the text's bytes
+        fs::write(file.path(), "07 ff fg 00\n").unwrap();
+        assert!(read_report_file(file.path(), false).is_err());
+        assert_eq!(
+            read_report_file(file.path(), true).unwrap(),
+            b"07 ff fg 00\n"
+        );
     }
 
     #[test]
     fn test_temperature_conversion() {
-        let mut config = Config::default();
-        config.temperature_unit = TemperatureUnit::Fahrenheit;
+        let config = Config {
+            temperature_unit: TemperatureUnit::Fahrenheit,
+            ..Config::default()
+        };
         let monitor = TemperatureMonitor::new(config);
 
         // Test known conversion: 0°C = 32°F
@@ -188,7 +562,7 @@ mod tests {
     #[test]
     fn test_sensor_availability() {
         let _monitor = TemperatureMonitor::new(Config::default());
-        let sensor_manager = monitor::SensorManager::new();
+        let sensor_manager = SensorManager::new();
         let sensor_info = sensor_manager.get_sensor_info();
 
         // Should have at least CPU sensor listed
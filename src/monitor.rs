@@ -1,52 +1,85 @@
-use crate::config::{Config, SensorType, TemperatureUnit};
-use crate::error::Result;
-use crate::sensor::{cpu_sensor::CpuSensor, gpu_sensor::GpuSensor};
-use log::{info, warn};
-use std::sync::mpsc;
+use crate::config::{Config, FilterConfig, RotationEntry, SensorType, SharedConfig};
+use crate::error::{OcypusError, Result};
+use crate::filter::{Filter, FilterChain};
+use crate::sensor::aggregate::{self, AggregateMode, AggregateSensor, Component};
+use crate::sensor::{self, Measurement, SensorReading, TemperatureSource};
+use log::{debug, info, warn};
+use std::fmt;
+use std::sync::{mpsc, Arc, RwLock};
 use std::thread;
+use std::time::Instant;
 
 /// Temperature monitoring service
 pub struct TemperatureMonitor {
-    config: Config,
+    config: SharedConfig,
     sensor_manager: SensorManager,
 }
 
 impl TemperatureMonitor {
     /// Create a new temperature monitor
     pub fn new(config: Config) -> Self {
+        Self::with_sensor_manager(config, SensorManager::new())
+    }
+
+    /// Create a temperature monitor using a custom set of sources
+    pub fn with_sensor_manager(config: Config, sensor_manager: SensorManager) -> Self {
         Self {
-            config,
-            sensor_manager: SensorManager::new(),
+            config: Arc::new(RwLock::new(config)),
+            sensor_manager,
         }
     }
 
     /// Start monitoring temperature in a separate thread
-    pub fn start_monitoring(&self) -> Result<mpsc::Receiver<f32>> {
-        let (tx, rx) = mpsc::channel::<f32>();
-        let config = self.config.clone();
+    ///
+    /// The thread re-reads the shared configuration on every tick, so a
+    /// reloaded interval, threshold, sensor or rotation takes effect without
+    /// a restart.
+    pub fn start_monitoring(&self) -> Result<mpsc::Receiver<Sample>> {
+        let (tx, rx) = mpsc::channel::<Sample>();
+        let shared_config = Arc::clone(&self.config);
         let sensor_manager = self.sensor_manager.clone();
+        let config = self.config();
+        let mut requested = Schedule::entries(&config);
+        let mut schedule = Schedule::new(&sensor_manager, requested.clone(), &config.filter)?;
 
         thread::spawn(move || {
             info!("Starting temperature monitoring thread");
-            info!("Using sensor: {}", config.sensor_type.as_str());
-            info!(
-                "Update interval: {} seconds",
-                config.update_interval.as_secs()
-            );
+            info!("Using sensor: {}", schedule);
 
             loop {
-                match sensor_manager.get_temperature(&config.sensor_type) {
-                    Ok(temp) => {
-                        Self::check_thresholds(temp, &config);
+                let config = crate::config::snapshot(&shared_config);
 
-                        if let Err(e) = tx.send(temp) {
-                            log::error!("Failed to send temperature: {}", e);
-                            break;
+                let entries = Schedule::entries(&config);
+                if entries != requested {
+                    match Schedule::new(&sensor_manager, entries.clone(), &config.filter) {
+                        Ok(new_schedule) => {
+                            info!("Switching sensor: {} -> {}", schedule, new_schedule);
+                            schedule = new_schedule;
                         }
+                        Err(e) => warn!(
+                            "Cannot switch to sensor {}, keeping {}: {}",
+                            describe(&entries),
+                            schedule,
+                            e
+                        ),
+                    }
+                    // Only try each new selection once to avoid log spam
+                    requested = entries;
+                } else if config.filter != schedule.filter {
+                    info!("Filter settings changed; restarting smoothing");
+                    schedule.set_filter(&config.filter);
+                }
+
+                // Continue monitoring even if every source fails
+                if let Some(sample) = schedule.sample(Instant::now()) {
+                    // Thresholds are in °C and don't apply to other metrics
+                    if sample.measurement.unit.is_temperature() {
+                        Self::check_thresholds(sample.measurement.value, &config);
                     }
-                    Err(e) => {
-                        warn!("Failed to get temperature: {}", e);
-                        // Continue monitoring even if one reading fails
+
+                    if let Err(e) = tx.send(sample) {
+                        log::error!("Failed to send reading: {}", e);
+                        break;
                     }
                 }
 
@@ -77,69 +110,361 @@ impl TemperatureMonitor {
     }
 
     /// Get a single temperature reading
-    #[allow(unused)]
     pub fn get_current_temperature(&self) -> Result<f32> {
         self.sensor_manager
-            .get_temperature(&self.config.sensor_type)
+            .get_temperature(&self.config().sensor_type)
     }
 
     /// Convert temperature to display unit
     pub fn convert_temperature(&self, temp_celsius: f32) -> f32 {
-        match self.config.temperature_unit {
-            TemperatureUnit::Celsius => temp_celsius,
-            TemperatureUnit::Fahrenheit => temp_celsius * 9.0 / 5.0 + 32.0,
+        self.config().temperature_unit.from_celsius(temp_celsius)
+    }
+
+    /// Get a snapshot of the current configuration
+    pub fn config(&self) -> Config {
+        crate::config::snapshot(&self.config)
+    }
+
+    /// Get a handle to the live configuration, for reloading
+    pub fn shared_config(&self) -> SharedConfig {
+        Arc::clone(&self.config)
+    }
+}
+
+/// A reading on its way to the display
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub measurement: Measurement,
+    /// Flag bytes marking which rotation source the reading comes from, if
+    /// configured
+    pub indicator: Option<[u8; 2]>,
+}
+
+/// The sources the monitor reads from: the configured sensor, or each
+/// rotation entry in turn
+struct Schedule {
+    slots: Vec<Slot>,
+    current: usize,
+    shown_since: Instant,
+    filter: FilterConfig,
+}
+
+/// A source in the schedule, with its own smoothing state
+struct Slot {
+    entry: RotationEntry,
+    source: Arc<dyn TemperatureSource>,
+    filter: FilterChain,
+}
+
+impl Schedule {
+    /// The rotation, or the single configured sensor if there is none
+    fn entries(config: &Config) -> Vec<RotationEntry> {
+        if config.rotation.is_empty() {
+            vec![RotationEntry::new(config.sensor_type.clone())]
+        } else {
+            config.rotation.clone()
         }
     }
 
-    /// Get the configuration
-    pub fn config(&self) -> &Config {
-        &self.config
+    fn new(
+        sensor_manager: &SensorManager,
+        entries: Vec<RotationEntry>,
+        filter: &FilterConfig,
+    ) -> Result<Self> {
+        let slots = entries
+            .into_iter()
+            .map(|entry| {
+                let source = sensor_manager.resolve(&entry.sensor)?;
+                Ok(Slot {
+                    entry,
+                    source,
+                    filter: FilterChain::new(filter),
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            slots,
+            current: 0,
+            shown_since: Instant::now(),
+            filter: filter.clone(),
+        })
+    }
+
+    /// Replace every source's filters, dropping their history
+    fn set_filter(&mut self, filter: &FilterConfig) {
+        for slot in &mut self.slots {
+            slot.filter = FilterChain::new(filter);
+        }
+        self.filter = filter.clone();
+    }
+
+    /// Read the source that should be on the display at `now`, moving on
+    /// once its dwell time is up. Sources that fail to read are skipped;
+    /// returns `None` if none of them can be read.
+    fn sample(&mut self, now: Instant) -> Option<Sample> {
+        if now.duration_since(self.shown_since) >= self.slots[self.current].entry.dwell {
+            self.advance(now);
+        }
+
+        for _ in 0..self.slots.len() {
+            let slot = &mut self.slots[self.current];
+            match slot.source.measure() {
+                Ok(mut measurement) => {
+                    measurement.value = slot.filter.apply(measurement.value);
+                    return Some(Sample {
+                        measurement,
+                        indicator: slot.entry.indicator,
+                    });
+                }
+                Err(e) => {
+                    warn!(
+                        "Failed to get {} from {}: {}",
+                        slot.source.unit(),
+                        slot.entry.sensor,
+                        e
+                    );
+                    self.advance(now);
+                }
+            }
+        }
+
+        None
+    }
+
+    fn advance(&mut self, now: Instant) {
+        self.current = (self.current + 1) % self.slots.len();
+        self.shown_since = now;
+        if self.slots.len() > 1 {
+            debug!("Showing {}", self.slots[self.current].entry.sensor);
+        }
     }
 }
 
-/// Sensor manager for handling different sensor types
-#[derive(Debug, Clone)]
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries: Vec<RotationEntry> = self.slots.iter().map(|s| s.entry.clone()).collect();
+        f.write_str(&describe(&entries))
+    }
+}
+
+/// `cpu`, or `cpu, gpu` for a rotation
+fn describe(entries: &[RotationEntry]) -> String {
+    let sensors: Vec<String> = entries.iter().map(|e| e.sensor.to_string()).collect();
+    sensors.join(", ")
+}
+
+/// Registry of temperature sources, looked up by name
+#[derive(Clone)]
 pub struct SensorManager {
-    // In a more complex implementation, this could manage sensor instances
-    // For now, it's just a marker type
+    sources: Vec<Arc<dyn TemperatureSource>>,
+}
+
+impl Default for SensorManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl SensorManager {
-    /// Create a new sensor manager
+    /// Create a sensor manager with the built-in sources
     pub fn new() -> Self {
-        Self {}
+        Self {
+            sources: sensor::default_sources(),
+        }
+    }
+
+    /// Create a sensor manager without any sources
+    pub fn empty() -> Self {
+        Self {
+            sources: Vec::new(),
+        }
+    }
+
+    /// Register a source, replacing any existing source with the same name
+    pub fn register(&mut self, source: Arc<dyn TemperatureSource>) {
+        self.sources.retain(|s| s.name() != source.name());
+        self.sources.push(source);
+    }
+
+    /// Get all registered sources
+    pub fn sources(&self) -> &[Arc<dyn TemperatureSource>] {
+        &self.sources
+    }
+
+    /// Find the source for a sensor selection, applying its selector.
+    /// `max`, `mean` and `weighted` combine the sources listed in the
+    /// selector (e.g. `max:cpu,gpu`).
+    pub fn resolve(&self, sensor_type: &SensorType) -> Result<Arc<dyn TemperatureSource>> {
+        if let Some(mode) = AggregateMode::from_name(sensor_type.name()) {
+            return self.resolve_aggregate(mode, sensor_type.selector());
+        }
+
+        let source = self.source(sensor_type)?;
+        match sensor_type.selector() {
+            Some(selector) => source.select(selector),
+            None => Ok(Arc::clone(source)),
+        }
+    }
+
+    /// Check that every sensor a configuration shows names a registered
+    /// source, or an aggregate of registered sources. Selectors are only
+    /// applied when the sensor is resolved, so nothing is read.
+    pub fn check_names(&self, config: &Config) -> Result<()> {
+        Schedule::entries(config)
+            .iter()
+            .try_for_each(|entry| self.check_name(&entry.sensor))
+    }
+
+    /// Check one sensor selection, recursing into aggregate components
+    fn check_name(&self, sensor_type: &SensorType) -> Result<()> {
+        if AggregateMode::from_name(sensor_type.name()).is_none() {
+            return self.source(sensor_type).map(|_| ());
+        }
+
+        let selector = sensor_type
+            .selector()
+            .unwrap_or(aggregate::DEFAULT_COMPONENTS);
+        for (sensor, _) in aggregate::parse_components(selector)? {
+            self.check_name(&sensor)?;
+        }
+        Ok(())
+    }
+
+    /// Check that a configuration can be monitored: every sensor it shows
+    /// resolves, and at least one of them can be read
+    pub fn check_config(&self, config: &Config) -> Result<()> {
+        let entries = Schedule::entries(config);
+        let mut available = false;
+        for entry in &entries {
+            available |= self.resolve(&entry.sensor)?.is_available();
+        }
+
+        if available {
+            Ok(())
+        } else {
+            Err(OcypusError::Sensor(format!(
+                "{} cannot be read",
+                describe(&entries)
+            )))
+        }
+    }
+
+    /// Find the registered source a sensor selection names
+    fn source(&self, sensor_type: &SensorType) -> Result<&Arc<dyn TemperatureSource>> {
+        self.sources
+            .iter()
+            .find(|s| s.name() == sensor_type.name())
+            .ok_or_else(|| {
+                let names: Vec<&str> = self
+                    .sources
+                    .iter()
+                    .map(|s| s.name())
+                    .chain(AggregateMode::ALL.map(AggregateMode::name))
+                    .collect();
+                OcypusError::InvalidSensorType(
+                    sensor_type.to_string(),
+                    format!("Supported types: {}", names.join(", ")),
+                )
+            })
+    }
+
+    /// Build an aggregate over the sources in `selector`, or CPU and GPU
+    fn resolve_aggregate(
+        &self,
+        mode: AggregateMode,
+        selector: Option<&str>,
+    ) -> Result<Arc<dyn TemperatureSource>> {
+        let components =
+            aggregate::parse_components(selector.unwrap_or(aggregate::DEFAULT_COMPONENTS))?
+                .into_iter()
+                .map(|(sensor, weight)| {
+                    if AggregateMode::from_name(sensor.name()).is_some() {
+                        return Err(OcypusError::Config(format!(
+                            "Aggregate sensors cannot be nested ('{}')",
+                            sensor
+                        )));
+                    }
+                    let source = self.resolve(&sensor)?;
+                    Ok(Component {
+                        sensor,
+                        weight,
+                        source,
+                    })
+                })
+                .collect::<Result<_>>()?;
+
+        Ok(Arc::new(AggregateSensor::new(mode, components)?))
     }
 
     /// Get temperature from the specified sensor
     pub fn get_temperature(&self, sensor_type: &SensorType) -> Result<f32> {
-        match sensor_type {
-            SensorType::Cpu => CpuSensor::get_temperature(),
-            SensorType::Gpu => GpuSensor::get_temperature(),
-        }
+        self.resolve(sensor_type)?.read()
     }
 
     /// Check if a sensor is available
-    #[allow(unused)]
     pub fn is_sensor_available(&self, sensor_type: &SensorType) -> bool {
-        match sensor_type {
-            SensorType::Cpu => CpuSensor::is_available(),
-            SensorType::Gpu => GpuSensor::is_available(),
-        }
+        self.resolve(sensor_type)
+            .map(|source| source.is_available())
+            .unwrap_or(false)
+    }
+
+    /// List every reading of every registered source
+    pub fn discover(&self) -> Vec<SensorReading> {
+        self.sources
+            .iter()
+            .flat_map(|source| source.discover())
+            .collect()
     }
 
     /// Get information about available sensors
-    #[allow(unused)]
     pub fn get_sensor_info(&self) -> Vec<(SensorType, bool)> {
-        vec![
-            (SensorType::Cpu, self.is_sensor_available(&SensorType::Cpu)),
-            (SensorType::Gpu, self.is_sensor_available(&SensorType::Gpu)),
-        ]
+        self.sources
+            .iter()
+            .map(|source| (SensorType::new(source.name()), source.is_available()))
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::TemperatureUnit;
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    struct MockSource {
+        name: &'static str,
+        temp: Option<f32>,
+    }
+
+    impl TemperatureSource for MockSource {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn is_available(&self) -> bool {
+            self.temp.is_some()
+        }
+
+        fn read(&self) -> Result<f32> {
+            self.temp
+                .ok_or_else(|| OcypusError::Sensor("mock sensor failure".to_string()))
+        }
+    }
+
+    fn mock_manager() -> SensorManager {
+        let mut manager = SensorManager::empty();
+        manager.register(Arc::new(MockSource {
+            name: "mock",
+            temp: Some(42.5),
+        }));
+        manager.register(Arc::new(MockSource {
+            name: "broken",
+            temp: None,
+        }));
+        manager
+    }
 
     #[test]
     fn test_temperature_monitor_creation() {
@@ -171,19 +496,272 @@ mod tests {
 
     #[test]
     fn test_temperature_conversion() {
-        let mut config = Config::default();
-        config.temperature_unit = TemperatureUnit::Celsius;
+        let config = Config {
+            temperature_unit: TemperatureUnit::Celsius,
+            ..Config::default()
+        };
         let monitor = TemperatureMonitor::new(config);
 
         // Test Celsius conversion (should be identity)
         assert_eq!(monitor.convert_temperature(25.0), 25.0);
 
         // Test Fahrenheit conversion
-        let mut config_f = Config::default();
-        config_f.temperature_unit = TemperatureUnit::Fahrenheit;
+        let config_f = Config {
+            temperature_unit: TemperatureUnit::Fahrenheit,
+            ..Config::default()
+        };
         let monitor_f = TemperatureMonitor::new(config_f);
 
         // 25°C = 77°F
         assert!((monitor_f.convert_temperature(25.0) - 77.0).abs() < 0.1);
     }
+
+    #[test]
+    fn test_sensor_manager_resolve() {
+        let manager = mock_manager();
+        let mock = SensorType::new("mock");
+        assert_eq!(manager.get_temperature(&mock).unwrap(), 42.5);
+        assert!(manager.is_sensor_available(&mock));
+        assert!(!manager.is_sensor_available(&SensorType::new("broken")));
+
+        let err = manager
+            .resolve(&SensorType::new("missing"))
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(
+            err.contains("mock, broken, max"),
+            "unexpected error: {}",
+            err
+        );
+
+        // Mock sources don't accept selectors
+        let selected = SensorType::from_str("mock:0").unwrap();
+        assert!(manager.resolve(&selected).is_err());
+    }
+
+    #[test]
+    fn test_sensor_manager_check_names() {
+        let manager = mock_manager();
+        let config = |sensor: &str| Config {
+            sensor_type: SensorType::from_str(sensor).unwrap(),
+            ..Config::default()
+        };
+
+        // Registered sources pass whether or not they can be read; the
+        // selector isn't applied
+        for sensor in ["mock", "broken", "mock:0", "max:mock,broken"] {
+            assert!(manager.check_names(&config(sensor)).is_ok(), "{}", sensor);
+        }
+        // Built-in names are unknown to a registry without them
+        for sensor in ["cpu", "max:mock,cpu", "max"] {
+            assert!(manager.check_names(&config(sensor)).is_err(), "{}", sensor);
+        }
+    }
+
+    #[test]
+    fn test_sensor_manager_discover() {
+        let readings = mock_manager().discover();
+        assert_eq!(readings.len(), 2);
+        assert_eq!(readings[0].sensor, "mock");
+        assert_eq!(readings[0].value, Some(42.5));
+        assert_eq!(readings[1].value, None);
+    }
+
+    #[test]
+    fn test_monitor_with_mock_source() {
+        let config = Config {
+            sensor_type: SensorType::new("mock"),
+            update_interval: Duration::from_millis(10),
+            ..Config::default()
+        };
+        let monitor = TemperatureMonitor::with_sensor_manager(config, mock_manager());
+
+        let rx = monitor.start_monitoring().unwrap();
+        let sample = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(sample.measurement, Measurement::celsius(42.5));
+        assert_eq!(sample.indicator, None);
+    }
+
+    #[test]
+    fn test_monitor_unknown_sensor() {
+        let config = Config {
+            sensor_type: SensorType::new("missing"),
+            ..Config::default()
+        };
+        let monitor = TemperatureMonitor::with_sensor_manager(config, mock_manager());
+        assert!(monitor.start_monitoring().is_err());
+    }
+
+    #[test]
+    fn test_monitor_follows_config_changes() {
+        let mut manager = mock_manager();
+        manager.register(Arc::new(MockSource {
+            name: "other",
+            temp: Some(60.0),
+        }));
+
+        let config = Config {
+            sensor_type: SensorType::new("mock"),
+            update_interval: Duration::from_millis(10),
+            ..Config::default()
+        };
+        let monitor = TemperatureMonitor::with_sensor_manager(config, manager);
+        let rx = monitor.start_monitoring().unwrap();
+        let first = rx.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(first.measurement.value, 42.5);
+
+        monitor.shared_config().write().unwrap().sensor_type = SensorType::new("other");
+        let switched = rx.iter().take(50).any(|s| s.measurement.value == 60.0);
+        assert!(switched, "monitor did not switch to the new sensor");
+    }
+
+    fn entry(sensor: &str, dwell_secs: u64, indicator: Option<[u8; 2]>) -> RotationEntry {
+        RotationEntry {
+            sensor: SensorType::new(sensor),
+            dwell: Duration::from_secs(dwell_secs),
+            indicator,
+        }
+    }
+
+    #[test]
+    fn test_rotation_dwell_and_skip() {
+        let mut manager = mock_manager();
+        manager.register(Arc::new(MockSource {
+            name: "other",
+            temp: Some(60.0),
+        }));
+        let entries = vec![
+            entry("mock", 5, Some([0xff, 0x01])),
+            entry("broken", 5, Some([0xff, 0x02])),
+            entry("other", 2, Some([0xff, 0x03])),
+        ];
+        let mut schedule = Schedule::new(&manager, entries, &FilterConfig::default()).unwrap();
+        assert_eq!(schedule.to_string(), "mock, broken, other");
+
+        let t0 = schedule.shown_since;
+        let at = |secs| t0 + Duration::from_secs(secs);
+        let shown = |sample: Option<Sample>| sample.map(|s| (s.measurement.value, s.indicator));
+
+        assert_eq!(
+            shown(schedule.sample(at(0))),
+            Some((42.5, Some([0xff, 0x01])))
+        );
+        assert_eq!(
+            shown(schedule.sample(at(4))),
+            Some((42.5, Some([0xff, 0x01])))
+        );
+        // The broken source is skipped and the next one gets a full dwell
+        assert_eq!(
+            shown(schedule.sample(at(5))),
+            Some((60.0, Some([0xff, 0x03])))
+        );
+        assert_eq!(
+            shown(schedule.sample(at(6))),
+            Some((60.0, Some([0xff, 0x03])))
+        );
+        assert_eq!(
+            shown(schedule.sample(at(7))),
+            Some((42.5, Some([0xff, 0x01])))
+        );
+
+        let filter = FilterConfig::default();
+        let mut broken = Schedule::new(&manager, vec![entry("broken", 5, None)], &filter).unwrap();
+        assert_eq!(broken.sample(t0), None);
+        assert!(Schedule::new(&manager, vec![entry("missing", 5, None)], &filter).is_err());
+    }
+
+    #[test]
+    fn test_monitor_rotation() {
+        let mut manager = mock_manager();
+        manager.register(Arc::new(MockSource {
+            name: "other",
+            temp: Some(60.0),
+        }));
+
+        let mut first = entry("mock", 0, None);
+        first.dwell = Duration::from_millis(30);
+        let mut second = entry("other", 0, Some([0x00, 0x01]));
+        second.dwell = Duration::from_millis(30);
+        let config = Config {
+            sensor_type: SensorType::new("missing"),
+            rotation: vec![first, second],
+            update_interval: Duration::from_millis(10),
+            ..Config::default()
+        };
+        let monitor = TemperatureMonitor::with_sensor_manager(config, manager);
+        let rx = monitor.start_monitoring().unwrap();
+
+        let samples: Vec<Sample> = rx.iter().take(50).collect();
+        assert!(samples
+            .iter()
+            .any(|s| s.measurement.value == 42.5 && s.indicator.is_none()));
+        assert!(samples
+            .iter()
+            .any(|s| s.measurement.value == 60.0 && s.indicator == Some([0x00, 0x01])));
+    }
+
+    #[test]
+    fn test_sensor_manager_aggregate() {
+        let mut manager = mock_manager();
+        manager.register(Arc::new(MockSource {
+            name: "other",
+            temp: Some(60.0),
+        }));
+
+        let resolve = |sensor: &str| manager.resolve(&SensorType::from_str(sensor).unwrap());
+        assert_eq!(resolve("max:mock,other").unwrap().read().unwrap(), 60.0);
+        assert_eq!(resolve("mean:mock,other").unwrap().read().unwrap(), 51.25);
+        assert_eq!(
+            resolve("weighted:mock=3,other=1").unwrap().read().unwrap(),
+            46.875
+        );
+        // A failing component is left out
+        assert_eq!(resolve("max:broken,mock").unwrap().read().unwrap(), 42.5);
+
+        assert!(resolve("max:mock,missing").is_err());
+        assert!(resolve("max:mock,max:other").is_err());
+    }
+
+    /// Source replaying a fixed series of readings
+    struct SeriesSource(std::sync::Mutex<Vec<f32>>);
+
+    impl TemperatureSource for SeriesSource {
+        fn name(&self) -> &str {
+            "series"
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+
+        fn read(&self) -> Result<f32> {
+            let mut series = self.0.lock().unwrap();
+            Ok(series.remove(0))
+        }
+    }
+
+    #[test]
+    fn test_schedule_applies_filters() {
+        let mut manager = SensorManager::empty();
+        manager.register(Arc::new(SeriesSource(std::sync::Mutex::new(vec![
+            41.9, 42.1, 41.8, 42.2, 44.0, 44.0,
+        ]))));
+        let filter = FilterConfig {
+            hysteresis: 0.5,
+            ..FilterConfig::default()
+        };
+        let mut schedule =
+            Schedule::new(&manager, vec![entry("series", 5, None)], &filter).unwrap();
+
+        let now = schedule.shown_since;
+        let mut shown = || schedule.sample(now).unwrap().measurement.value;
+        assert_eq!([shown(), shown(), shown(), shown()], [41.9; 4]);
+        assert_eq!(shown(), 44.0);
+
+        // New settings start from scratch
+        schedule.set_filter(&FilterConfig::default());
+        assert_eq!(schedule.sample(now).unwrap().measurement.value, 44.0);
+        assert_eq!(schedule.filter, FilterConfig::default());
+    }
 }
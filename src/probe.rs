@@ -0,0 +1,439 @@
+//! Reading back what the display reports about itself.
+//!
+//! The L24 protocol is only known for the temperature output report, so
+//! this dumps everything the device answers with, decoding the fields that
+//! are understood, to help map out the rest.
+
+use crate::config::REPORT_ID;
+use crate::device::{DeviceInfo, DeviceManager};
+use crate::error::{OcypusError, Result};
+use crate::protocol::Report;
+use serde::{Serialize, Serializer};
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Kind of report declared in a report descriptor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportKind {
+    Input,
+    Output,
+    Feature,
+}
+
+/// A report declared in the report descriptor
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReportLayout {
+    pub kind: ReportKind,
+    /// Report ID, or 0 if the device doesn't number its reports
+    pub report_id: u8,
+    /// Payload length in bytes, excluding the report ID
+    pub length: usize,
+}
+
+/// How a report was obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportSource {
+    /// `GET_REPORT(Feature)` on the control endpoint
+    Feature,
+    /// `GET_REPORT(Input)` on the control endpoint
+    Input,
+    /// Unsolicited report from the interrupt endpoint
+    Interrupt,
+}
+
+/// A decoded report field
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReportField {
+    pub name: String,
+    pub value: String,
+}
+
+/// One report read from the device, or why reading it failed
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportDump {
+    pub source: ReportSource,
+    pub report_id: u8,
+    #[serde(serialize_with = "serialize_hex")]
+    pub data: Option<Vec<u8>>,
+    pub error: Option<String>,
+    pub fields: Vec<ReportField>,
+}
+
+/// Everything read back from a display
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceProbe {
+    pub device: DeviceInfo,
+    #[serde(serialize_with = "serialize_hex")]
+    pub descriptor: Option<Vec<u8>>,
+    pub layouts: Vec<ReportLayout>,
+    pub reports: Vec<ReportDump>,
+}
+
+/// Read the report descriptor and every report it declares from the
+/// connected device, then wait up to `timeout` for an unsolicited report.
+/// Without a usable descriptor, report `REPORT_ID` is requested.
+pub fn probe(manager: &mut DeviceManager, timeout: Duration) -> Result<DeviceProbe> {
+    let device = manager
+        .connected_device()
+        .cloned()
+        .ok_or_else(|| OcypusError::Device("Device not connected".to_string()))?;
+
+    let descriptor = manager.report_descriptor().ok();
+    let layouts = descriptor
+        .as_deref()
+        .map(parse_report_descriptor)
+        .unwrap_or_default();
+
+    let mut requests: Vec<(ReportSource, u8)> = layouts
+        .iter()
+        .filter_map(|layout| match layout.kind {
+            ReportKind::Feature => Some((ReportSource::Feature, layout.report_id)),
+            ReportKind::Input => Some((ReportSource::Input, layout.report_id)),
+            ReportKind::Output => None,
+        })
+        .collect();
+    if requests.is_empty() {
+        requests = vec![
+            (ReportSource::Feature, REPORT_ID),
+            (ReportSource::Input, REPORT_ID),
+        ];
+    }
+
+    let mut reports: Vec<ReportDump> = requests
+        .into_iter()
+        .map(|(source, report_id)| {
+            let result = match source {
+                ReportSource::Feature => manager.get_feature_report(report_id),
+                _ => manager.get_input_report(report_id),
+            };
+            ReportDump::new(source, report_id, result)
+        })
+        .collect();
+
+    if let Some(data) = manager.read_report(timeout)? {
+        reports.push(ReportDump::new(ReportSource::Interrupt, data[0], Ok(data)));
+    }
+
+    Ok(DeviceProbe {
+        device,
+        descriptor,
+        layouts,
+        reports,
+    })
+}
+
+impl ReportDump {
+    fn new(source: ReportSource, report_id: u8, result: Result<Vec<u8>>) -> Self {
+        match result {
+            Ok(data) => Self {
+                source,
+                report_id,
+                fields: decode_report(&data),
+                data: Some(data),
+                error: None,
+            },
+            Err(e) => Self {
+                source,
+                report_id,
+                data: None,
+                error: Some(e.to_string()),
+                fields: Vec::new(),
+            },
+        }
+    }
+}
+
+/// Decode the fields of a report that are understood: the report ID, and
+/// for `REPORT_ID` reports the fields of the display `Report`
+pub fn decode_report(report: &[u8]) -> Vec<ReportField> {
+    let field = |name: &str, value: String| ReportField {
+        name: name.to_string(),
+        value,
+    };
+
+    let Some(&report_id) = report.first() else {
+        return Vec::new();
+    };
+    let mut fields = vec![field("report_id", format!("{:#04x}", report_id))];
+
+    if report_id == REPORT_ID {
+        if let Ok(decoded) = Report::decode(report) {
+            fields.push(field("flags", to_hex(&decoded.flags)));
+            fields.push(field(
+                "digits",
+                match decoded.value() {
+                    Some(value) => format!("{} ({:03})", to_hex(&decoded.digits), value),
+                    None => to_hex(&decoded.digits),
+                },
+            ));
+        }
+    }
+
+    fields
+}
+
+/// List the reports declared by a HID report descriptor, in order of first
+/// appearance
+pub fn parse_report_descriptor(descriptor: &[u8]) -> Vec<ReportLayout> {
+    #[derive(Clone, Copy, Default)]
+    struct Globals {
+        report_size: u32,
+        report_count: u32,
+        report_id: u8,
+    }
+
+    let mut globals = Globals::default();
+    let mut stack = Vec::new();
+    let mut bits: Vec<(ReportKind, u8, u32)> = Vec::new();
+
+    let mut i = 0;
+    while i < descriptor.len() {
+        let prefix = descriptor[i];
+
+        // Long item: 0xfe, data size, long tag, data
+        if prefix == 0xfe {
+            let size = descriptor.get(i + 1).copied().unwrap_or(0) as usize;
+            i += 3 + size;
+            continue;
+        }
+
+        let size = match prefix & 0x03 {
+            3 => 4,
+            n => n as usize,
+        };
+        let Some(data) = descriptor.get(i + 1..i + 1 + size) else {
+            break;
+        };
+        let value = data
+            .iter()
+            .rev()
+            .fold(0u32, |acc, b| (acc << 8) | u32::from(*b));
+        i += 1 + size;
+
+        let item_type = (prefix >> 2) & 0x03;
+        let tag = prefix >> 4;
+        match (item_type, tag) {
+            // Main items: Input, Output, Feature
+            (0, 0x8 | 0x9 | 0xb) => {
+                let kind = match tag {
+                    0x8 => ReportKind::Input,
+                    0x9 => ReportKind::Output,
+                    _ => ReportKind::Feature,
+                };
+                let added = globals.report_size.saturating_mul(globals.report_count);
+                match bits
+                    .iter_mut()
+                    .find(|(k, id, _)| *k == kind && *id == globals.report_id)
+                {
+                    Some((_, _, total)) => *total = total.saturating_add(added),
+                    None => bits.push((kind, globals.report_id, added)),
+                }
+            }
+            // Global items
+            (1, 0x7) => globals.report_size = value,
+            (1, 0x8) => globals.report_id = value as u8,
+            (1, 0x9) => globals.report_count = value,
+            (1, 0xa) => stack.push(globals),
+            (1, 0xb) => globals = stack.pop().unwrap_or_default(),
+            _ => {}
+        }
+    }
+
+    bits.into_iter()
+        .map(|(kind, report_id, total)| ReportLayout {
+            kind,
+            report_id,
+            length: total.div_ceil(8) as usize,
+        })
+        .collect()
+}
+
+/// Format bytes as space-separated hex
+pub fn to_hex(data: &[u8]) -> String {
+    data.iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn serialize_hex<S: Serializer>(
+    data: &Option<Vec<u8>>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    match data {
+        Some(data) => serializer.serialize_str(&to_hex(data)),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Classic hex dump: offset, 16 bytes in hex and their printable ASCII
+pub fn hex_dump(data: &[u8]) -> String {
+    let mut out = String::new();
+
+    for (line, chunk) in data.chunks(16).enumerate() {
+        let _ = write!(out, "{:04x}  ", line * 16);
+        for i in 0..16 {
+            match chunk.get(i) {
+                Some(b) => {
+                    let _ = write!(out, "{:02x} ", b);
+                }
+                None => out.push_str("   "),
+            }
+            if i == 7 {
+                out.push(' ');
+            }
+        }
+
+        out.push_str(" |");
+        out.extend(chunk.iter().map(|b| {
+            if b.is_ascii_graphic() || *b == b' ' {
+                *b as char
+            } else {
+                '.'
+            }
+        }));
+        out.push_str("|\n");
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MockTransport;
+
+    /// Vendor-defined page with 63-byte input and output reports, ID 7
+    const VENDOR_DESCRIPTOR: &[u8] = &[
+        0x06, 0x00, 0xff, // Usage Page (Vendor Defined 0xFF00)
+        0x09, 0x01, // Usage (0x01)
+        0xa1, 0x01, // Collection (Application)
+        0x85, 0x07, //   Report ID (7)
+        0x15, 0x00, //   Logical Minimum (0)
+        0x26, 0xff, 0x00, //   Logical Maximum (255)
+        0x75, 0x08, //   Report Size (8)
+        0x95, 0x3f, //   Report Count (63)
+        0x09, 0x01, //   Usage (0x01)
+        0x81, 0x02, //   Input (Data, Var, Abs)
+        0x09, 0x01, //   Usage (0x01)
+        0x91, 0x02, //   Output (Data, Var, Abs)
+        0xc0, // End Collection
+    ];
+
+    #[test]
+    fn test_parse_report_descriptor() {
+        let layouts = parse_report_descriptor(VENDOR_DESCRIPTOR);
+        assert_eq!(
+            layouts,
+            vec![
+                ReportLayout {
+                    kind: ReportKind::Input,
+                    report_id: 7,
+                    length: 63,
+                },
+                ReportLayout {
+                    kind: ReportKind::Output,
+                    report_id: 7,
+                    length: 63,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_descriptor_push_pop_and_truncation() {
+        let descriptor = [
+            0x75, 0x08, // Report Size (8)
+            0x95, 0x04, // Report Count (4)
+            0xa4, // Push
+            0x85, 0x02, // Report ID (2)
+            0x95, 0x10, // Report Count (16)
+            0xb1, 0x02, // Feature
+            0xb4, // Pop
+            0x81, 0x02, // Input, report ID 0 with 4 bytes
+            0x95, // truncated item
+        ];
+        let layouts = parse_report_descriptor(&descriptor);
+        assert_eq!(layouts.len(), 2);
+        assert_eq!(layouts[0].kind, ReportKind::Feature);
+        assert_eq!((layouts[0].report_id, layouts[0].length), (2, 16));
+        assert_eq!((layouts[1].report_id, layouts[1].length), (0, 4));
+    }
+
+    #[test]
+    fn test_decode_report() {
+        let mut report = vec![0u8; 64];
+        report[..6].copy_from_slice(&[REPORT_ID, 0xff, 0xff, 0, 4, 2]);
+        let fields = decode_report(&report);
+        let value = |name: &str| {
+            fields
+                .iter()
+                .find(|f| f.name == name)
+                .unwrap()
+                .value
+                .clone()
+        };
+        assert_eq!(value("report_id"), "0x07");
+        assert_eq!(value("flags"), "ff ff");
+        assert_eq!(value("digits"), "00 04 02 (042)");
+
+        assert_eq!(decode_report(&[0x01, 0x02]).len(), 1);
+        assert!(decode_report(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_hex_dump() {
+        let data: Vec<u8> = (0x41..0x41 + 18).collect();
+        let dump = hex_dump(&data);
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(
+            lines[0],
+            "0000  41 42 43 44 45 46 47 48  49 4a 4b 4c 4d 4e 4f 50  |ABCDEFGHIJKLMNOP|"
+        );
+        assert!(lines[1].starts_with("0010  51 52 "));
+        assert!(lines[1].ends_with("|QR|"));
+        assert_eq!(to_hex(&[0x07, 0xff]), "07 ff");
+    }
+
+    #[test]
+    fn test_probe_with_mock_device() {
+        let transport = MockTransport::new();
+        transport.plug(MockTransport::ocypus_device("/dev/hidraw3", Some("A")));
+        transport.set_report_descriptor(VENDOR_DESCRIPTOR.to_vec());
+        transport.set_input_report(vec![0x07, 0x01, 0x00, 0x00, 0x00, 0x05]);
+        transport.push_input(vec![0x07, 0xaa]);
+
+        let mut manager = DeviceManager::with_transport(Box::new(transport), None);
+        manager.connect().unwrap();
+        let probe = probe(&mut manager, Duration::from_millis(10)).unwrap();
+
+        assert_eq!(probe.device.serial.as_deref(), Some("A"));
+        assert_eq!(probe.layouts.len(), 2);
+        assert_eq!(probe.reports.len(), 2);
+        assert_eq!(probe.reports[0].source, ReportSource::Input);
+        assert_eq!(probe.reports[0].fields[2].value, "00 00 05 (005)");
+        assert_eq!(probe.reports[1].source, ReportSource::Interrupt);
+
+        let json = serde_json::to_value(&probe).unwrap();
+        assert_eq!(json["reports"][1]["data"], "07 aa");
+        assert_eq!(json["layouts"][0]["kind"], "input");
+    }
+
+    #[test]
+    fn test_probe_without_descriptor_falls_back_to_report_id() {
+        let transport = MockTransport::new();
+        transport.plug(MockTransport::ocypus_device("/dev/hidraw3", None));
+        transport.set_feature_report(vec![REPORT_ID, 0x01]);
+
+        let mut manager = DeviceManager::with_transport(Box::new(transport), None);
+        manager.connect().unwrap();
+        let probe = probe(&mut manager, Duration::ZERO).unwrap();
+
+        assert!(probe.descriptor.is_none());
+        assert_eq!(probe.reports.len(), 2);
+        assert_eq!(probe.reports[0].data, Some(vec![REPORT_ID, 0x01]));
+        assert!(probe.reports[1].error.is_some());
+    }
+}
@@ -0,0 +1,302 @@
+//! Framing of the 64-byte output report understood by the L24.
+//!
+//! ```text
+//! byte  0      report ID (0x07)
+//! bytes 1-2    flag bytes; meaning unknown, the vendor software always sends 0xff 0xff
+//!              (a decimal point, if the L24 has one, would be lit from here)
+//! bytes 3-5    digit slots: hundreds, tens, ones (0-9 each)
+//! bytes 6-63   reserved; ignored by the L24 and sent as zero
+//! ```
+
+use crate::config::{REPORT_ID, REPORT_LENGTH};
+use crate::error::{OcypusError, Result};
+
+/// Number of digit slots on the display
+pub const DIGIT_COUNT: usize = 3;
+
+/// Offset of the first flag byte
+const FLAGS_OFFSET: usize = 1;
+
+/// Offset of the first digit slot
+const DIGITS_OFFSET: usize = FLAGS_OFFSET + 2;
+
+/// Offset of the reserved payload
+const RESERVED_OFFSET: usize = DIGITS_OFFSET + DIGIT_COUNT;
+
+/// Length of the reserved payload
+pub const RESERVED_LENGTH: usize = REPORT_LENGTH - RESERVED_OFFSET;
+
+/// Flag bytes sent by the vendor software
+pub const DEFAULT_FLAGS: [u8; 2] = [0xff, 0xff];
+
+/// Largest value the digit slots can show
+pub const MAX_VALUE: u32 = 999;
+
+/// A display output report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// HID report ID
+    pub report_id: u8,
+    /// Two bytes following the report ID. Their meaning hasn't been mapped
+    /// out yet; `DEFAULT_FLAGS` shows the digits as-is.
+    pub flags: [u8; 2],
+    /// Hundreds, tens and ones digit, most significant first. Leading zeros
+    /// are shown as zeros.
+    pub digits: [u8; DIGIT_COUNT],
+    /// Trailing bytes, unused by the L24
+    pub reserved: [u8; RESERVED_LENGTH],
+}
+
+impl Default for Report {
+    fn default() -> Self {
+        Self {
+            report_id: REPORT_ID,
+            flags: DEFAULT_FLAGS,
+            digits: [0; DIGIT_COUNT],
+            reserved: [0; RESERVED_LENGTH],
+        }
+    }
+}
+
+impl Report {
+    /// A report showing `value`, clamped to `MAX_VALUE`
+    pub fn with_value(value: u32) -> Self {
+        let value = value.min(MAX_VALUE);
+        Self {
+            digits: [
+                (value / 100) as u8,
+                ((value / 10) % 10) as u8,
+                (value % 10) as u8,
+            ],
+            ..Self::default()
+        }
+    }
+
+    /// A report showing `tenths / 10` with one decimal place, e.g. 459 as
+    /// `45.9`, sending `flags` to light the decimal point. `None` if the
+    /// value needs more than three digits.
+    pub fn with_tenths(tenths: u32, flags: [u8; 2]) -> Option<Self> {
+        (tenths <= MAX_VALUE).then(|| Self {
+            flags,
+            ..Self::with_value(tenths)
+        })
+    }
+
+    /// The number shown by the digit slots, or `None` if a slot holds
+    /// something other than a decimal digit
+    pub fn value(&self) -> Option<u32> {
+        self.digits.iter().try_fold(0u32, |acc, digit| {
+            (*digit <= 9).then(|| acc * 10 + u32::from(*digit))
+        })
+    }
+
+    /// Serialize into the bytes written to the device
+    pub fn encode(&self) -> [u8; REPORT_LENGTH] {
+        let mut bytes = [0u8; REPORT_LENGTH];
+        bytes[0] = self.report_id;
+        bytes[FLAGS_OFFSET..DIGITS_OFFSET].copy_from_slice(&self.flags);
+        bytes[DIGITS_OFFSET..RESERVED_OFFSET].copy_from_slice(&self.digits);
+        bytes[RESERVED_OFFSET..].copy_from_slice(&self.reserved);
+        bytes
+    }
+
+    /// Parse a report. Short reports (as returned by some reads) must still
+    /// cover the digit slots; missing reserved bytes are taken as zero.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < RESERVED_OFFSET || bytes.len() > REPORT_LENGTH {
+            return Err(OcypusError::Protocol(format!(
+                "report is {} bytes, expected {} to {}",
+                bytes.len(),
+                RESERVED_OFFSET,
+                REPORT_LENGTH
+            )));
+        }
+
+        let mut report = Self {
+            report_id: bytes[0],
+            ..Self::default()
+        };
+        report
+            .flags
+            .copy_from_slice(&bytes[FLAGS_OFFSET..DIGITS_OFFSET]);
+        report
+            .digits
+            .copy_from_slice(&bytes[DIGITS_OFFSET..RESERVED_OFFSET]);
+        let reserved = &bytes[RESERVED_OFFSET..];
+        report.reserved[..reserved.len()].copy_from_slice(reserved);
+
+        Ok(report)
+    }
+}
+
+/// Parse hex bytes such as `07 ff ff 00 04 02`, `07:ff:ff` or `0x07,0xff`.
+/// Whitespace, `:` and `,` separate bytes; `#` starts a comment running to
+/// the end of the line. Unseparated runs are read two digits at a time.
+pub fn parse_hex(text: &str) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default();
+        for token in line.split(|c: char| c.is_whitespace() || c == ',' || c == ':') {
+            let digits = token
+                .strip_prefix("0x")
+                .or_else(|| token.strip_prefix("0X"))
+                .unwrap_or(token);
+            if digits.is_empty() {
+                continue;
+            }
+            if digits.len() % 2 != 0 && digits.len() > 2 {
+                return Err(OcypusError::Protocol(format!(
+                    "odd number of hex digits in '{}'",
+                    token
+                )));
+            }
+
+            for chunk in digits.as_bytes().chunks(2) {
+                let chunk = std::str::from_utf8(chunk).unwrap_or_default();
+                let byte = u8::from_str_radix(chunk, 16)
+                    .map_err(|_| OcypusError::Protocol(format!("invalid hex byte '{}'", token)))?;
+                bytes.push(byte);
+            }
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Zero-pad `bytes` to a full report, rejecting empty or oversized input
+pub fn pad_report(bytes: &[u8]) -> Result<[u8; REPORT_LENGTH]> {
+    if bytes.is_empty() || bytes.len() > REPORT_LENGTH {
+        return Err(OcypusError::Protocol(format!(
+            "report is {} bytes, expected 1 to {}",
+            bytes.len(),
+            REPORT_LENGTH
+        )));
+    }
+
+    let mut report = [0u8; REPORT_LENGTH];
+    report[..bytes.len()].copy_from_slice(bytes);
+    Ok(report)
+}
+
+/// Copies of `base` with the byte at `index` set to each value in 0..=255
+pub fn sweep(
+    base: [u8; REPORT_LENGTH],
+    index: usize,
+) -> Result<impl Iterator<Item = (u8, [u8; REPORT_LENGTH])>> {
+    if index >= REPORT_LENGTH {
+        return Err(OcypusError::Protocol(format!(
+            "sweep index {} is outside the {}-byte report",
+            index, REPORT_LENGTH
+        )));
+    }
+
+    Ok((0..=u8::MAX).map(move |value| {
+        let mut report = base;
+        report[index] = value;
+        (value, report)
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_layout() {
+        let bytes = Report::with_value(42).encode();
+        assert_eq!(bytes.len(), REPORT_LENGTH);
+        assert_eq!(&bytes[..6], &[REPORT_ID, 0xff, 0xff, 0, 4, 2]);
+        assert!(bytes[6..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut reserved = [0u8; RESERVED_LENGTH];
+        reserved[0] = 0x5a;
+        reserved[RESERVED_LENGTH - 1] = 0xa5;
+        let report = Report {
+            report_id: 0x02,
+            flags: [0x01, 0x80],
+            digits: [9, 0, 7],
+            reserved,
+        };
+
+        let decoded = Report::decode(&report.encode()).unwrap();
+        assert_eq!(decoded, report);
+        assert_eq!(decoded.value(), Some(907));
+    }
+
+    #[test]
+    fn test_decode_short_and_invalid_lengths() {
+        let report = Report::decode(&[REPORT_ID, 0xff, 0xff, 1, 2, 3]).unwrap();
+        assert_eq!(report.value(), Some(123));
+        assert_eq!(report.reserved, [0; RESERVED_LENGTH]);
+
+        assert!(Report::decode(&[REPORT_ID, 0xff, 0xff, 1, 2]).is_err());
+        assert!(Report::decode(&[0u8; REPORT_LENGTH + 1]).is_err());
+    }
+
+    #[test]
+    fn test_value_clamping_and_non_digits() {
+        assert_eq!(Report::with_value(1500).digits, [9, 9, 9]);
+        assert_eq!(Report::with_value(5).digits, [0, 0, 5]);
+
+        let report = Report {
+            digits: [0, 0x0a, 1],
+            ..Report::default()
+        };
+        assert_eq!(report.value(), None);
+    }
+
+    #[test]
+    fn test_with_tenths() {
+        let report = Report::with_tenths(459, [0xff, 0x7f]).unwrap();
+        assert_eq!(&report.encode()[..6], &[REPORT_ID, 0xff, 0x7f, 4, 5, 9]);
+        assert_eq!(
+            Report::with_tenths(7, [0xff, 0x7f]).unwrap().digits,
+            [0, 0, 7]
+        );
+        assert_eq!(Report::with_tenths(1000, [0xff, 0x7f]), None);
+    }
+
+    #[test]
+    fn test_parse_hex() {
+        assert_eq!(
+            parse_hex("07 ff FF 00").unwrap(),
+            vec![0x07, 0xff, 0xff, 0x00]
+        );
+        assert_eq!(parse_hex("0x07,0xff:1").unwrap(), vec![0x07, 0xff, 0x01]);
+        assert_eq!(parse_hex("07ffff").unwrap(), vec![0x07, 0xff, 0xff]);
+        assert_eq!(
+            parse_hex("07 # report id\nff ff # flags\n").unwrap(),
+            vec![0x07, 0xff, 0xff]
+        );
+        assert!(parse_hex("").unwrap().is_empty());
+
+        assert!(parse_hex("07 fg").is_err());
+        assert!(parse_hex("07f").is_err());
+    }
+
+    #[test]
+    fn test_pad_report() {
+        let report = pad_report(&[0x07, 0xff]).unwrap();
+        assert_eq!(&report[..3], &[0x07, 0xff, 0x00]);
+
+        assert!(pad_report(&[]).is_err());
+        assert!(pad_report(&[0u8; REPORT_LENGTH + 1]).is_err());
+    }
+
+    #[test]
+    fn test_sweep() {
+        let base = Report::with_value(42).encode();
+        let reports: Vec<_> = sweep(base, 1).unwrap().collect();
+        assert_eq!(reports.len(), 256);
+        assert_eq!(reports[0].1[1], 0x00);
+        assert_eq!(reports[200].0, 200);
+        assert_eq!(reports[255].1[1], 0xff);
+        assert!(reports.iter().all(|(_, r)| r[2] == 0xff && r[5] == 2));
+
+        assert!(sweep(base, REPORT_LENGTH).is_err());
+    }
+}
@@ -0,0 +1,301 @@
+use crate::config::{Args, Config, SharedConfig};
+use crate::error::{OcypusError, Result};
+use crate::monitor::SensorManager;
+use log::{debug, info, warn};
+use signal_hook::consts::SIGHUP;
+use signal_hook::iterator::Signals;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// How often to check for SIGHUP and configuration file changes
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Reloads the configuration into running monitors on SIGHUP or when the
+/// configuration file changes
+pub struct ConfigReloader {
+    args: Args,
+    displays: Vec<SharedConfig>,
+    watched: Option<(PathBuf, Option<SystemTime>)>,
+    sensors: SensorManager,
+}
+
+impl ConfigReloader {
+    /// Create a reloader that rebuilds configuration from `args` (and the
+    /// configuration file they point to) into each display's configuration,
+    /// in the order returned by `Config::load_displays`
+    pub fn new(args: Args, displays: Vec<SharedConfig>) -> Self {
+        let watched = displays
+            .first()
+            .and_then(|config| crate::config::snapshot(config).source)
+            .map(|path| {
+                let last_modified = modified(&path);
+                (path, last_modified)
+            });
+
+        Self {
+            args,
+            displays,
+            watched,
+            sensors: SensorManager::new(),
+        }
+    }
+
+    /// Check reloaded sensors against the registry the monitors run with,
+    /// rather than the built-in sources. Sensor names are only checked here,
+    /// so a custom source registered with the monitors can be switched to.
+    pub fn with_sensors(mut self, sensors: SensorManager) -> Self {
+        self.sensors = sensors;
+        self
+    }
+
+    /// Re-read and validate the configuration and resolve its sensors,
+    /// swapping it in on success. On failure the current configuration is
+    /// kept.
+    pub fn reload(&mut self) -> Result<()> {
+        // Remember this version of the file even if it turns out invalid, so
+        // a broken edit is reported once rather than on every poll
+        if let Some((path, last_modified)) = &mut self.watched {
+            *last_modified = modified(path);
+        }
+
+        let new_configs = Config::load_displays(&self.args)?
+            .into_iter()
+            .map(Config::validate)
+            .collect::<Result<Vec<_>>>()?;
+
+        // A sensor that can't be resolved or read would fail every tick
+        for config in &new_configs {
+            self.sensors.check_config(config)?;
+        }
+
+        // Devices stay connected across reloads, so the set of displays
+        // can't change without a restart
+        let devices_changed = new_configs.len() != self.displays.len()
+            || new_configs
+                .iter()
+                .zip(&self.displays)
+                .any(|(new, old)| new.device != crate::config::snapshot(old).device);
+        if devices_changed {
+            return Err(OcypusError::Config(
+                "the set of displays changed; restart to apply".to_string(),
+            ));
+        }
+
+        self.watched = new_configs[0]
+            .source
+            .as_ref()
+            .map(|path| (path.clone(), modified(path)));
+
+        for (new_config, shared) in new_configs.into_iter().zip(&self.displays) {
+            info!(
+                "Configuration reloaded: sensor {}, unit °{}, interval {}s",
+                new_config.sensor_type,
+                new_config.temperature_unit.as_char(),
+                new_config.update_interval.as_secs()
+            );
+
+            *shared.write().unwrap_or_else(|e| e.into_inner()) = new_config;
+        }
+        Ok(())
+    }
+
+    /// Check whether the watched configuration file changed since the last load
+    pub fn file_changed(&self) -> bool {
+        match &self.watched {
+            Some((path, last_modified)) => modified(path) != *last_modified,
+            None => false,
+        }
+    }
+
+    /// Reload, logging why the old configuration is kept on failure
+    fn reload_or_keep(&mut self, reason: &str) {
+        info!("Reloading configuration ({})", reason);
+        if let Err(e) = self.reload() {
+            warn!("Keeping previous configuration: {}", e);
+        }
+    }
+
+    /// Spawn a thread that reloads on SIGHUP and on configuration file changes
+    pub fn spawn(mut self) -> Result<()> {
+        let mut signals = Signals::new([SIGHUP])?;
+
+        thread::spawn(move || {
+            debug!("Configuration reloader started");
+
+            loop {
+                if signals.pending().next().is_some() {
+                    self.reload_or_keep("SIGHUP");
+                } else if self.file_changed() {
+                    self.reload_or_keep("configuration file changed");
+                }
+
+                thread::sleep(POLL_INTERVAL);
+            }
+        });
+
+        Ok(())
+    }
+}
+
+/// Modification time of a file, if it exists
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SensorType;
+    use crate::sensor::hwmon::HwmonSensor;
+    use crate::sensor::TemperatureSource;
+    use clap::Parser;
+    use std::sync::{Arc, RwLock};
+    use tempfile::NamedTempFile;
+
+    /// Stand-in for a built-in source that can always be read
+    struct Fixed(&'static str);
+
+    impl TemperatureSource for Fixed {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+
+        fn read(&self) -> Result<f32> {
+            Ok(40.0)
+        }
+    }
+
+    /// Readable `cpu`, `gpu` and `thermal` sources, and an `hwmon` source
+    /// without any chips
+    fn sensors() -> SensorManager {
+        let mut sensors = SensorManager::empty();
+        for name in ["cpu", "gpu", "thermal"] {
+            sensors.register(Arc::new(Fixed(name)));
+        }
+        sensors.register(Arc::new(HwmonSensor::with_root("/nonexistent")));
+        sensors
+    }
+
+    fn reloader_for(file: &NamedTempFile) -> ConfigReloader {
+        let args = Args::parse_from([
+            "ocypus-l24-digital",
+            "--config",
+            file.path().to_str().unwrap(),
+        ]);
+        let displays = Config::load_displays(&args)
+            .unwrap()
+            .into_iter()
+            .map(|config| Arc::new(RwLock::new(config.validate().unwrap())))
+            .collect();
+        ConfigReloader::new(args, displays).with_sensors(sensors())
+    }
+
+    #[test]
+    fn test_reload_swaps_config() {
+        let file = NamedTempFile::new().unwrap();
+        fs::write(file.path(), "sensor = \"cpu\"\ninterval = 1\n").unwrap();
+        let mut reloader = reloader_for(&file);
+
+        fs::write(file.path(), "sensor = \"gpu\"\ninterval = 3\n").unwrap();
+        reloader.reload().unwrap();
+
+        let config = crate::config::snapshot(&reloader.displays[0]);
+        assert_eq!(config.sensor_type, SensorType::new("gpu"));
+        assert_eq!(config.update_interval, Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_invalid_reload_keeps_old_config() {
+        let file = NamedTempFile::new().unwrap();
+        fs::write(file.path(), "interval = 2\n").unwrap();
+        let mut reloader = reloader_for(&file);
+
+        fs::write(file.path(), "high_threshold = 10.0\nlow_threshold = 50.0\n").unwrap();
+        assert!(reloader.reload().is_err());
+
+        fs::write(file.path(), "interval = \"soon\"\n").unwrap();
+        assert!(reloader.reload().is_err());
+        assert!(!reloader.file_changed());
+
+        let config = crate::config::snapshot(&reloader.displays[0]);
+        assert_eq!(config.update_interval, Duration::from_secs(2));
+        assert_eq!(config.high_threshold, 80.0);
+    }
+
+    #[test]
+    fn test_reload_with_unknown_sensor_keeps_old_config() {
+        let file = NamedTempFile::new().unwrap();
+        fs::write(file.path(), "sensor = \"cpu\"\n").unwrap();
+        let mut reloader = reloader_for(&file);
+
+        // An unknown source, a bad selector and a channel that isn't there
+        for sensor in ["nope", "gpu:1/vram", "hwmon:missing"] {
+            fs::write(file.path(), format!("sensor = \"{}\"\n", sensor)).unwrap();
+            assert!(reloader.reload().is_err(), "{}", sensor);
+        }
+
+        let config = crate::config::snapshot(&reloader.displays[0]);
+        assert_eq!(config.sensor_type, SensorType::new("cpu"));
+    }
+
+    #[test]
+    fn test_reload_to_custom_source() {
+        let file = NamedTempFile::new().unwrap();
+        fs::write(file.path(), "sensor = \"cpu\"\n").unwrap();
+        let mut sensors = sensors();
+        sensors.register(Arc::new(Fixed("nvme")));
+        let mut reloader = reloader_for(&file).with_sensors(sensors);
+
+        fs::write(file.path(), "sensor = \"nvme\"\n").unwrap();
+        reloader.reload().unwrap();
+        let config = crate::config::snapshot(&reloader.displays[0]);
+        assert_eq!(config.sensor_type, SensorType::new("nvme"));
+
+        fs::write(file.path(), "sensor = \"max:cpu,nvme\"\n").unwrap();
+        reloader.reload().unwrap();
+        let config = crate::config::snapshot(&reloader.displays[0]);
+        assert_eq!(config.sensor_type.to_string(), "max:cpu,nvme");
+    }
+
+    #[test]
+    fn test_reload_multiple_displays() {
+        let file = NamedTempFile::new().unwrap();
+        let two_displays = "[[display]]\ndevice = \"serial:A\"\nsensor = \"cpu\"\n\
+                            [[display]]\ndevice = \"serial:B\"\nsensor = \"gpu\"\n";
+        fs::write(file.path(), two_displays).unwrap();
+        let mut reloader = reloader_for(&file);
+        assert_eq!(reloader.displays.len(), 2);
+
+        fs::write(file.path(), two_displays.replace("\"gpu\"", "\"thermal\"")).unwrap();
+        reloader.reload().unwrap();
+        let second = crate::config::snapshot(&reloader.displays[1]);
+        assert_eq!(second.sensor_type, SensorType::new("thermal"));
+
+        // Changing which devices are driven needs a restart
+        fs::write(file.path(), two_displays.replace("serial:B", "serial:C")).unwrap();
+        assert!(reloader.reload().is_err());
+        let second = crate::config::snapshot(&reloader.displays[1]);
+        assert_eq!(second.sensor_type, SensorType::new("thermal"));
+    }
+
+    #[test]
+    fn test_file_change_detection() {
+        let file = NamedTempFile::new().unwrap();
+        fs::write(file.path(), "interval = 2\n").unwrap();
+        let mut reloader = reloader_for(&file);
+        assert!(!reloader.file_changed());
+
+        let later = SystemTime::now() + Duration::from_secs(10);
+        file.as_file().set_modified(later).unwrap();
+        assert!(reloader.file_changed());
+
+        reloader.reload().unwrap();
+        assert!(!reloader.file_changed());
+    }
+}
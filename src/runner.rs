@@ -0,0 +1,368 @@
+use crate::config::{ReadoutConfig, TemperatureUnit};
+use crate::device::{self, DeviceManager};
+use crate::error::Result;
+use crate::monitor::{Sample, TemperatureMonitor};
+use crate::protocol::Report;
+use crate::sensor::MetricUnit;
+use log::{debug, error, info};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+/// How often to check for hotplug events between readings
+const HOTPLUG_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Feed readings from `receiver` to the device until the monitor stops.
+///
+/// When a send fails the device is reconnected under the configured
+/// `ReconnectPolicy`; readings taken meanwhile are dropped in favour of the
+/// newest one. Returns an error if the policy gives up. Readings are skipped
+/// while hotplug events report the display as unplugged.
+///
+/// In rotation mode the monitor hands over each source in turn; a reading's
+/// indicator, if any, replaces the default flag bytes so the sources can be
+/// told apart. The indicator takes precedence over decimal point flags, so
+/// such readings are shown as whole numbers.
+pub fn main_loop(
+    device_manager: &mut DeviceManager,
+    temperature_monitor: &TemperatureMonitor,
+    receiver: mpsc::Receiver<Sample>,
+) -> Result<()> {
+    info!("Starting temperature monitoring loop");
+
+    loop {
+        let mut sample = match receiver.recv_timeout(HOTPLUG_POLL_INTERVAL) {
+            Ok(sample) => sample,
+            Err(RecvTimeoutError::Timeout) => {
+                device_manager.poll_hotplug();
+                continue;
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+
+        // Nothing to do until the display is plugged back in
+        device_manager.poll_hotplug();
+        if device_manager.is_unplugged() {
+            debug!("Display unplugged; skipping reading");
+            continue;
+        }
+
+        let config = temperature_monitor.config();
+        let unit = config.temperature_unit;
+        let readout = config.readout;
+
+        let report = match send(device_manager, &sample, unit, &readout) {
+            Ok(report) => report,
+            Err(e) => {
+                error!("Device communication error: {}", e);
+
+                device_manager.set_reconnect_policy(config.reconnect);
+                device_manager.reconnect_with_backoff()?;
+                info!("Successfully reconnected to device");
+
+                sample = latest_reading(&receiver, sample);
+                match send(device_manager, &sample, unit, &readout) {
+                    Ok(report) => report,
+                    Err(retry_err) => {
+                        error!("Failed to send reading after reconnection: {}", retry_err);
+                        continue;
+                    }
+                }
+            }
+        };
+
+        // Log what the digits show, after scaling and rounding
+        let decimal = readout
+            .decimal_point()
+            .filter(|_| sample.indicator.is_none());
+        info!(
+            "Display {}: {}",
+            sample.measurement.unit,
+            shown(&report, decimal, sample.measurement.unit, unit)
+        );
+    }
+
+    Ok(())
+}
+
+/// Show a reading, marked with its indicator if it has one, returning the
+/// report that was sent
+fn send(
+    device_manager: &mut DeviceManager,
+    sample: &Sample,
+    unit: TemperatureUnit,
+    readout: &ReadoutConfig,
+) -> Result<Report> {
+    let report = match sample.indicator {
+        Some(indicator) => {
+            let readout = ReadoutConfig {
+                decimal: false,
+                ..*readout
+            };
+            let mut report = device::display_report(sample.measurement, unit, &readout);
+            report.flags = indicator;
+            report
+        }
+        None => device::display_report(sample.measurement, unit, readout),
+    };
+    device_manager.send_report(&report)?;
+    Ok(report)
+}
+
+/// The number `report` shows, with the decimal point if it carries the
+/// `decimal` flags, followed by the unit of the digits
+fn shown(
+    report: &Report,
+    decimal: Option<[u8; 2]>,
+    metric: MetricUnit,
+    unit: TemperatureUnit,
+) -> String {
+    let value = report.value().unwrap_or_default();
+    let number = match decimal {
+        Some(flags) if report.flags == flags => format!("{}.{}", value / 10, value % 10),
+        _ => value.to_string(),
+    };
+    let suffix = match metric {
+        MetricUnit::Rpm => " (tens of RPM)".to_string(),
+        _ => metric.suffix(unit),
+    };
+    format!("{}{}", number, suffix)
+}
+
+/// Drain readings that queued up while the device was unavailable,
+/// returning the newest
+fn latest_reading(receiver: &mpsc::Receiver<Sample>, mut sample: Sample) -> Sample {
+    let mut dropped = 0;
+    while let Ok(newer) = receiver.try_recv() {
+        sample = newer;
+        dropped += 1;
+    }
+    if dropped > 0 {
+        debug!("Dropped {} stale readings", dropped);
+    }
+    sample
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, ReconnectPolicy};
+    use crate::hotplug::HidrawPoller;
+    use crate::sensor::Measurement;
+    use crate::transport::MockTransport;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const PATH: &str = "/dev/hidraw3";
+
+    fn connected_manager(transport: &MockTransport) -> DeviceManager {
+        transport.plug(MockTransport::ocypus_device(PATH, Some("A")));
+        let mut manager = DeviceManager::with_transport(Box::new(transport.clone()), None);
+        manager.connect().unwrap();
+        manager
+    }
+
+    /// Run the loop over a fixed series of readings
+    fn run(manager: &mut DeviceManager, config: Config, readings: &[f32]) -> Result<()> {
+        let samples: Vec<Sample> = readings
+            .iter()
+            .map(|reading| Sample {
+                measurement: Measurement::celsius(*reading),
+                indicator: None,
+            })
+            .collect();
+        run_samples(manager, config, &samples)
+    }
+
+    fn run_samples(manager: &mut DeviceManager, config: Config, samples: &[Sample]) -> Result<()> {
+        let (tx, rx) = mpsc::channel();
+        for sample in samples {
+            tx.send(*sample).unwrap();
+        }
+        drop(tx);
+
+        main_loop(manager, &TemperatureMonitor::new(config), rx)
+    }
+
+    fn digits(reports: &[Vec<u8>]) -> Vec<[u8; 3]> {
+        reports.iter().map(|r| [r[3], r[4], r[5]]).collect()
+    }
+
+    #[test]
+    fn test_main_loop_sends_every_reading() {
+        let transport = MockTransport::new();
+        let mut manager = connected_manager(&transport);
+
+        let config = Config {
+            temperature_unit: TemperatureUnit::Fahrenheit,
+            ..Config::default()
+        };
+        run(&mut manager, config, &[25.0, 100.0]).unwrap();
+
+        assert_eq!(digits(&transport.reports()), vec![[0, 7, 7], [2, 1, 2]]);
+    }
+
+    #[test]
+    fn test_main_loop_retries_after_write_failure() {
+        let transport = MockTransport::new();
+        let mut manager = connected_manager(&transport);
+
+        transport.fail_writes(1);
+        run(&mut manager, Config::default(), &[41.0]).unwrap();
+        run(&mut manager, Config::default(), &[42.0]).unwrap();
+
+        // The failed reading is resent on a fresh handle
+        assert_eq!(digits(&transport.reports()), vec![[0, 4, 1], [0, 4, 2]]);
+        assert_eq!(transport.open_count(), 2);
+    }
+
+    #[test]
+    fn test_main_loop_drops_stale_readings() {
+        let transport = MockTransport::new();
+        let mut manager = connected_manager(&transport);
+
+        // Readings queued behind the failed one are skipped for the newest
+        transport.fail_writes(1);
+        run(&mut manager, Config::default(), &[41.0, 42.0, 43.0]).unwrap();
+
+        assert_eq!(digits(&transport.reports()), vec![[0, 4, 3]]);
+    }
+
+    #[test]
+    fn test_main_loop_gives_up_after_max_attempts() {
+        let transport = MockTransport::new();
+        let mut manager = connected_manager(&transport);
+
+        transport.unplug(PATH);
+        let config = Config {
+            reconnect: ReconnectPolicy {
+                initial_delay: Duration::ZERO,
+                max_delay: Duration::ZERO,
+                jitter: 0.0,
+                max_attempts: Some(2),
+            },
+            ..Config::default()
+        };
+        assert!(run(&mut manager, config, &[41.0, 42.0]).is_err());
+        assert!(transport.reports().is_empty());
+    }
+
+    #[test]
+    fn test_main_loop_reconnects_after_replug() {
+        let transport = MockTransport::new();
+        let mut manager = connected_manager(&transport);
+
+        // The cooler drops off the bus and comes back as a new handle
+        transport.unplug(PATH);
+        transport.plug(MockTransport::ocypus_device(PATH, Some("A")));
+        run(&mut manager, Config::default(), &[55.0]).unwrap();
+
+        assert_eq!(digits(&transport.reports()), vec![[0, 5, 5]]);
+        assert!(manager.is_connected());
+    }
+
+    #[test]
+    fn test_main_loop_waits_for_replug() {
+        let transport = MockTransport::new();
+        let mut manager = connected_manager(&transport);
+
+        let sysfs = TempDir::new().unwrap();
+        fs::create_dir(sysfs.path().join("hidraw3")).unwrap();
+        manager.set_hotplug(Box::new(HidrawPoller::with_root(sysfs.path())));
+
+        // While unplugged, readings are skipped rather than spent on
+        // reconnection attempts that would exhaust the policy
+        transport.unplug(PATH);
+        fs::remove_dir(sysfs.path().join("hidraw3")).unwrap();
+        let config = Config {
+            reconnect: ReconnectPolicy {
+                max_attempts: Some(1),
+                ..ReconnectPolicy::default()
+            },
+            ..Config::default()
+        };
+        run(&mut manager, config.clone(), &[41.0, 42.0]).unwrap();
+        assert!(manager.is_unplugged());
+        assert!(transport.reports().is_empty());
+
+        transport.plug(MockTransport::ocypus_device(PATH, Some("A")));
+        fs::create_dir(sysfs.path().join("hidraw3")).unwrap();
+        run(&mut manager, config, &[43.0]).unwrap();
+        assert_eq!(digits(&transport.reports()), vec![[0, 4, 3]]);
+    }
+
+    #[test]
+    fn test_main_loop_sends_rotation_indicators() {
+        let transport = MockTransport::new();
+        let mut manager = connected_manager(&transport);
+
+        let samples = [
+            Sample {
+                measurement: Measurement::celsius(45.0),
+                indicator: Some([0xff, 0x01]),
+            },
+            Sample {
+                measurement: Measurement::celsius(61.0),
+                indicator: None,
+            },
+        ];
+        run_samples(&mut manager, Config::default(), &samples).unwrap();
+
+        let reports = transport.reports();
+        assert_eq!(&reports[0][1..6], &[0xff, 0x01, 0, 4, 5]);
+        assert_eq!(&reports[1][1..6], &[0xff, 0xff, 0, 6, 1]);
+    }
+
+    #[test]
+    fn test_main_loop_decimal_readout() {
+        let transport = MockTransport::new();
+        let mut manager = connected_manager(&transport);
+
+        let samples = [
+            Sample {
+                measurement: Measurement::celsius(45.9),
+                indicator: None,
+            },
+            // The rotation indicator owns the flag bytes
+            Sample {
+                measurement: Measurement::celsius(45.9),
+                indicator: Some([0xff, 0x01]),
+            },
+        ];
+        let config = Config {
+            readout: ReadoutConfig {
+                decimal: true,
+                decimal_flags: Some([0xff, 0x7f]),
+                ..ReadoutConfig::default()
+            },
+            ..Config::default()
+        };
+        run_samples(&mut manager, config, &samples).unwrap();
+
+        let reports = transport.reports();
+        assert_eq!(&reports[0][1..6], &[0xff, 0x7f, 4, 5, 9]);
+        assert_eq!(&reports[1][1..6], &[0xff, 0x01, 0, 4, 5]);
+    }
+
+    #[test]
+    fn test_shown_matches_the_display() {
+        let celsius = TemperatureUnit::Celsius;
+        let readout = ReadoutConfig::default();
+        let show = |measurement: Measurement, readout: &ReadoutConfig| {
+            let report = device::display_report(measurement, celsius, readout);
+            shown(&report, readout.decimal_point(), measurement.unit, celsius)
+        };
+
+        assert_eq!(show(Measurement::celsius(45.9), &readout), "45°C");
+        let rpm = Measurement::new(1458.0, MetricUnit::Rpm);
+        assert_eq!(show(rpm, &readout), "145 (tens of RPM)");
+
+        let decimal = ReadoutConfig {
+            decimal: true,
+            decimal_flags: Some([0xff, 0x7f]),
+            ..readout
+        };
+        assert_eq!(show(Measurement::celsius(45.9), &decimal), "45.9°C");
+        // Too wide for tenths, so shown whole
+        assert_eq!(show(Measurement::celsius(123.4), &decimal), "123°C");
+    }
+}
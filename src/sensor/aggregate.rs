@@ -0,0 +1,305 @@
+use crate::config::SensorType;
+use crate::error::{OcypusError, Result};
+use crate::sensor::{MetricUnit, TemperatureSource};
+use log::{debug, info};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+/// Components combined when an aggregate is given without a selector
+pub const DEFAULT_COMPONENTS: &str = "cpu,gpu";
+
+/// How an aggregate combines its components
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateMode {
+    /// The hottest component
+    Max,
+    /// The average of all components
+    Mean,
+    /// The average weighted by each component's `=weight`
+    Weighted,
+}
+
+impl AggregateMode {
+    /// All modes, in the order they are listed
+    pub const ALL: [AggregateMode; 3] = [
+        AggregateMode::Max,
+        AggregateMode::Mean,
+        AggregateMode::Weighted,
+    ];
+
+    /// Name used to select the mode (e.g. `max:cpu,gpu`)
+    pub fn name(self) -> &'static str {
+        match self {
+            AggregateMode::Max => "max",
+            AggregateMode::Mean => "mean",
+            AggregateMode::Weighted => "weighted",
+        }
+    }
+
+    /// The mode selected by a sensor name, if it names one
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.name() == name)
+    }
+}
+
+/// Parse a comma-separated component list such as `cpu,hwmon:nvme=0.5`.
+/// Weights default to 1.
+pub fn parse_components(selector: &str) -> Result<Vec<(SensorType, f32)>> {
+    selector
+        .split(',')
+        .map(|part| {
+            let (sensor, weight) = match part.rsplit_once('=') {
+                Some((sensor, weight)) => {
+                    let weight = weight.trim().parse::<f32>().map_err(|e| {
+                        OcypusError::Config(format!("Invalid weight in '{}': {}", part, e))
+                    })?;
+                    if !weight.is_finite() || weight < 0.0 {
+                        return Err(OcypusError::Config(format!(
+                            "Weight in '{}' must not be negative",
+                            part
+                        )));
+                    }
+                    (sensor, weight)
+                }
+                None => (part, 1.0),
+            };
+            Ok((SensorType::from_str(sensor)?, weight))
+        })
+        .collect()
+}
+
+/// One source combined by an aggregate
+pub struct Component {
+    pub sensor: SensorType,
+    pub weight: f32,
+    pub source: Arc<dyn TemperatureSource>,
+}
+
+/// A virtual source combining several others. Components that fail to read
+/// are left out, so one missing backend doesn't stop the reading.
+pub struct AggregateSensor {
+    mode: AggregateMode,
+    components: Vec<Component>,
+    /// Component that was hottest on the previous read, to log changes
+    hottest: Mutex<Option<String>>,
+}
+
+impl AggregateSensor {
+    /// Combine `components`, which must all report the same unit
+    pub fn new(mode: AggregateMode, components: Vec<Component>) -> Result<Self> {
+        let Some(first) = components.first() else {
+            return Err(OcypusError::Config(format!(
+                "Sensor '{}' needs at least one component",
+                mode.name()
+            )));
+        };
+
+        let unit = first.source.unit();
+        if let Some(other) = components.iter().find(|c| c.source.unit() != unit) {
+            return Err(OcypusError::Config(format!(
+                "Cannot combine {} ({}) with {} ({})",
+                first.sensor,
+                unit,
+                other.sensor,
+                other.source.unit()
+            )));
+        }
+
+        Ok(Self {
+            mode,
+            components,
+            hottest: Mutex::new(None),
+        })
+    }
+
+    /// Log when a different component becomes the hottest one
+    fn note_hottest(&self, sensor: &SensorType, value: f32) {
+        let name = sensor.to_string();
+        let mut hottest = self.hottest.lock().unwrap_or_else(|e| e.into_inner());
+        if hottest.as_deref() != Some(name.as_str()) {
+            info!("Hottest component: {} ({:.1})", name, value);
+            *hottest = Some(name);
+        }
+    }
+}
+
+impl TemperatureSource for AggregateSensor {
+    fn name(&self) -> &str {
+        self.mode.name()
+    }
+
+    /// Check if any component can be read
+    fn is_available(&self) -> bool {
+        self.components.iter().any(|c| c.source.is_available())
+    }
+
+    fn read(&self) -> Result<f32> {
+        let mut readings = Vec::with_capacity(self.components.len());
+        let mut errors = Vec::new();
+
+        for component in &self.components {
+            match component.source.read() {
+                Ok(value) => readings.push((component, value)),
+                Err(e) => {
+                    debug!("Skipping {}: {}", component.sensor, e);
+                    errors.push(format!("{}: {}", component.sensor, e));
+                }
+            }
+        }
+
+        let Some((hottest, max)) = readings
+            .iter()
+            .copied()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        else {
+            return Err(OcypusError::Sensor(format!(
+                "No component of '{}' could be read ({})",
+                self.mode.name(),
+                errors.join("; ")
+            )));
+        };
+        self.note_hottest(&hottest.sensor, max);
+
+        let weight = |c: &Component| match self.mode {
+            AggregateMode::Weighted => c.weight,
+            _ => 1.0,
+        };
+        match self.mode {
+            AggregateMode::Max => Ok(max),
+            AggregateMode::Mean | AggregateMode::Weighted => {
+                let total: f32 = readings.iter().map(|(c, _)| weight(c)).sum();
+                if total <= 0.0 {
+                    return Err(OcypusError::Sensor(
+                        "Components that could be read all have zero weight".to_string(),
+                    ));
+                }
+                let sum: f32 = readings.iter().map(|(c, v)| weight(c) * v).sum();
+                Ok(sum / total)
+            }
+        }
+    }
+
+    fn unit(&self) -> MetricUnit {
+        self.components[0].source.unit()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(&'static str, Option<f32>, MetricUnit);
+
+    impl TemperatureSource for Fixed {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn is_available(&self) -> bool {
+            self.1.is_some()
+        }
+
+        fn read(&self) -> Result<f32> {
+            self.1
+                .ok_or_else(|| OcypusError::Sensor(format!("{} is missing", self.0)))
+        }
+
+        fn unit(&self) -> MetricUnit {
+            self.2
+        }
+    }
+
+    fn component(name: &'static str, value: Option<f32>, weight: f32) -> Component {
+        Component {
+            sensor: SensorType::new(name),
+            weight,
+            source: Arc::new(Fixed(name, value, MetricUnit::Celsius)),
+        }
+    }
+
+    fn aggregate(mode: AggregateMode, components: Vec<Component>) -> AggregateSensor {
+        AggregateSensor::new(mode, components).unwrap()
+    }
+
+    #[test]
+    fn test_parse_components() {
+        let parts = parse_components("cpu, hwmon:nvme/Composite=0.5").unwrap();
+        assert_eq!(parts[0], (SensorType::new("cpu"), 1.0));
+        assert_eq!(
+            parts[1],
+            (SensorType::from_str("hwmon:nvme/Composite").unwrap(), 0.5)
+        );
+
+        assert!(parse_components("cpu=heavy").is_err());
+        assert!(parse_components("cpu=-1").is_err());
+        assert!(parse_components("cpu,,gpu").is_err());
+    }
+
+    #[test]
+    fn test_modes() {
+        let parts = || {
+            vec![
+                component("cpu", Some(60.0), 3.0),
+                component("gpu", Some(40.0), 1.0),
+            ]
+        };
+        assert_eq!(aggregate(AggregateMode::Max, parts()).read().unwrap(), 60.0);
+        assert_eq!(
+            aggregate(AggregateMode::Mean, parts()).read().unwrap(),
+            50.0
+        );
+        assert_eq!(
+            aggregate(AggregateMode::Weighted, parts()).read().unwrap(),
+            55.0
+        );
+    }
+
+    #[test]
+    fn test_tolerates_failing_components() {
+        let sensor = aggregate(
+            AggregateMode::Weighted,
+            vec![
+                component("cpu", Some(60.0), 1.0),
+                component("gpu", None, 3.0),
+            ],
+        );
+        assert!(sensor.is_available());
+        assert_eq!(sensor.read().unwrap(), 60.0);
+
+        let sensor = aggregate(
+            AggregateMode::Max,
+            vec![component("cpu", None, 1.0), component("gpu", None, 1.0)],
+        );
+        assert!(!sensor.is_available());
+        let err = sensor.read().unwrap_err().to_string();
+        assert!(err.contains("gpu is missing"), "unexpected error: {}", err);
+    }
+
+    #[test]
+    fn test_tracks_hottest_component() {
+        let sensor = aggregate(
+            AggregateMode::Mean,
+            vec![
+                component("cpu", Some(45.0), 1.0),
+                component("gpu", Some(70.0), 1.0),
+            ],
+        );
+        sensor.read().unwrap();
+        assert_eq!(sensor.hottest.lock().unwrap().as_deref(), Some("gpu"));
+    }
+
+    #[test]
+    fn test_rejects_mixed_units() {
+        let fan = Component {
+            sensor: SensorType::new("fan"),
+            weight: 1.0,
+            source: Arc::new(Fixed("fan", Some(900.0), MetricUnit::Rpm)),
+        };
+        let result = AggregateSensor::new(
+            AggregateMode::Max,
+            vec![component("cpu", Some(50.0), 1.0), fan],
+        );
+        assert!(result.is_err());
+        assert!(AggregateSensor::new(AggregateMode::Max, Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,258 @@
+use crate::error::{OcypusError, Result};
+use crate::sensor::{MetricUnit, SensorReading, TemperatureSource};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Default kernel CPU statistics file
+pub const DEFAULT_PROC_STAT: &str = "/proc/stat";
+
+/// How long to sample when there is no earlier reading to compare against
+const SAMPLE_WINDOW: Duration = Duration::from_millis(200);
+
+/// Cumulative CPU time counters of one `/proc/stat` line, in jiffies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CpuTimes {
+    /// Time spent on anything but idle and iowait
+    pub busy: u64,
+    /// Time spent in any state
+    pub total: u64,
+}
+
+impl CpuTimes {
+    /// Utilisation in percent between an `earlier` sample and this one, or
+    /// `None` if no time has passed
+    pub fn usage_since(&self, earlier: &CpuTimes) -> Option<f32> {
+        let total = self.total.checked_sub(earlier.total)?;
+        let busy = self.busy.saturating_sub(earlier.busy);
+        (total > 0).then(|| (busy.min(total) as f32 / total as f32) * 100.0)
+    }
+}
+
+/// Parse the `cpu` and `cpuN` lines of `/proc/stat`
+pub fn parse_proc_stat(text: &str) -> Vec<(String, CpuTimes)> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let name = fields.next()?;
+            if !name.starts_with("cpu") {
+                return None;
+            }
+
+            // user nice system idle iowait irq softirq steal; guest time is
+            // already included in user and nice
+            let counters: Vec<u64> = fields.take(8).map_while(|f| f.parse().ok()).collect();
+            if counters.len() < 4 {
+                return None;
+            }
+
+            let total = counters.iter().sum();
+            let idle = counters[3] + counters.get(4).copied().unwrap_or(0);
+            Some((
+                name.to_string(),
+                CpuTimes {
+                    busy: total - idle,
+                    total,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// CPU utilisation from `/proc/stat`
+#[derive(Debug)]
+pub struct CpuLoadSensor {
+    path: PathBuf,
+    /// `cpu` for all CPUs, or `cpuN` for a single one
+    cpu: String,
+    /// Counters from the previous read, so each read covers the time since
+    previous: Mutex<Option<CpuTimes>>,
+}
+
+impl Default for CpuLoadSensor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpuLoadSensor {
+    /// Create a sensor reading the overall load from `/proc/stat`
+    pub fn new() -> Self {
+        Self::with_path(DEFAULT_PROC_STAT, "cpu")
+    }
+
+    /// Create a sensor reading the given `cpu`/`cpuN` line of a custom file
+    pub fn with_path(path: impl Into<PathBuf>, cpu: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            cpu: cpu.into(),
+            previous: Mutex::new(None),
+        }
+    }
+
+    /// Read the counters of every CPU line
+    fn sample_all(&self) -> Result<Vec<(String, CpuTimes)>> {
+        let text = fs::read_to_string(&self.path).map_err(|e| {
+            OcypusError::Sensor(format!("Failed to read {}: {}", self.path.display(), e))
+        })?;
+        Ok(parse_proc_stat(&text))
+    }
+
+    /// Read the counters of the configured CPU line
+    fn sample(&self) -> Result<CpuTimes> {
+        self.sample_all()?
+            .into_iter()
+            .find(|(name, _)| *name == self.cpu)
+            .map(|(_, times)| times)
+            .ok_or_else(|| {
+                OcypusError::Sensor(format!(
+                    "'{}' not found in {}",
+                    self.cpu,
+                    self.path.display()
+                ))
+            })
+    }
+}
+
+impl TemperatureSource for CpuLoadSensor {
+    fn name(&self) -> &str {
+        "load"
+    }
+
+    /// Check if the configured CPU line can be read
+    fn is_available(&self) -> bool {
+        self.sample().is_ok()
+    }
+
+    /// Get the utilisation since the previous read. The first read samples
+    /// over a short window instead.
+    fn read(&self) -> Result<f32> {
+        let mut previous = self.previous.lock().unwrap_or_else(|e| e.into_inner());
+        let mut current = self.sample()?;
+
+        let usage = match previous.and_then(|earlier| current.usage_since(&earlier)) {
+            Some(usage) => usage,
+            None => {
+                let earlier = current;
+                thread::sleep(SAMPLE_WINDOW);
+                current = self.sample()?;
+                current.usage_since(&earlier).unwrap_or(0.0)
+            }
+        };
+
+        *previous = Some(current);
+        Ok(usage)
+    }
+
+    fn unit(&self) -> MetricUnit {
+        MetricUnit::Percent
+    }
+
+    fn discover(&self) -> Vec<SensorReading> {
+        let Ok(earlier) = self.sample_all() else {
+            return Vec::new();
+        };
+        thread::sleep(SAMPLE_WINDOW);
+        let current = self.sample_all().unwrap_or_default();
+
+        earlier
+            .iter()
+            .map(|(name, before)| {
+                let value = current
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .and_then(|(_, after)| after.usage_since(before));
+                SensorReading {
+                    sensor: if name == "cpu" {
+                        "load".to_string()
+                    } else {
+                        format!("load:{}", name)
+                    },
+                    backend: "procfs".to_string(),
+                    chip: "stat".to_string(),
+                    label: name.clone(),
+                    value,
+                    unit: MetricUnit::Percent,
+                    default: *name == self.cpu,
+                }
+            })
+            .collect()
+    }
+
+    /// Select a single CPU, as `cpu3` or just `3`
+    fn select(&self, selector: &str) -> Result<Arc<dyn TemperatureSource>> {
+        let cpu = if selector.chars().all(|c| c.is_ascii_digit()) {
+            format!("cpu{}", selector)
+        } else {
+            selector.to_string()
+        };
+        Ok(Arc::new(Self::with_path(self.path.clone(), cpu)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const STAT: &str = "\
+cpu  4705 150 1120 16250 520 0 45 0 0 0
+cpu0 2500 100 600 8000 300 0 30 0 0 0
+cpu1 2205 50 520 8250 220 0 15 0 0 0
+intr 114930548 113199788 3 0 5 263 0 4 [...]
+ctxt 1990473
+";
+
+    #[test]
+    fn test_parse_proc_stat() {
+        let cpus = parse_proc_stat(STAT);
+        assert_eq!(cpus.len(), 3);
+        assert_eq!(cpus[0].0, "cpu");
+        assert_eq!(
+            cpus[0].1,
+            CpuTimes {
+                busy: 4705 + 150 + 1120 + 45,
+                total: 4705 + 150 + 1120 + 16250 + 520 + 45,
+            }
+        );
+        assert_eq!(cpus[2].0, "cpu1");
+        assert!(parse_proc_stat("cpu 1 2\n").is_empty());
+    }
+
+    #[test]
+    fn test_usage_since() {
+        let earlier = CpuTimes {
+            busy: 100,
+            total: 400,
+        };
+        let later = CpuTimes {
+            busy: 175,
+            total: 500,
+        };
+        assert_eq!(later.usage_since(&earlier), Some(75.0));
+        assert_eq!(earlier.usage_since(&earlier), None);
+        // Counters going backwards (e.g. a CPU coming back online)
+        assert_eq!(earlier.usage_since(&later), None);
+    }
+
+    #[test]
+    fn test_read_between_samples() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("stat");
+        fs::write(&path, "cpu  100 0 0 300 0 0 0 0\ncpu0 50 0 0 150 0 0 0 0\n").unwrap();
+
+        let sensor = CpuLoadSensor::with_path(&path, "cpu");
+        assert_eq!(sensor.unit(), MetricUnit::Percent);
+        // Nothing changes during the first sample window
+        assert_eq!(sensor.read().unwrap(), 0.0);
+
+        fs::write(&path, "cpu  150 0 0 350 0 0 0 0\ncpu0 90 0 0 160 0 0 0 0\n").unwrap();
+        assert_eq!(sensor.read().unwrap(), 50.0);
+
+        let cpu0 = sensor.select("0").unwrap();
+        assert!(cpu0.is_available());
+        assert!(!sensor.select("cpu7").unwrap().is_available());
+    }
+}
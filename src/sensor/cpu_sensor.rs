@@ -1,65 +1,62 @@
-use crate::error::{OcypusError, Result};
-use regex::Regex;
+use crate::error::Result;
+use crate::sensor::hwmon::HwmonSensor;
+use crate::sensor::lm_sensors::{self, LmSensorsSensor, SensorsTemp};
+use crate::sensor::{SensorReading, TemperatureSource};
+use log::debug;
 use std::process::Command;
 
 /// CPU temperature sensor
 pub struct CpuSensor;
 
-impl CpuSensor {
-    /// Get the CPU temperature using the best available method
-    pub fn get_temperature() -> Result<f32> {
-        let output = Command::new("sensors").output().map_err(|e| {
-            OcypusError::Sensor(format!("Failed to execute sensors command: {}", e))
-        })?;
-
-        if !output.status.success() {
-            return Err(OcypusError::Sensor(
-                "sensors command returned non-zero exit status".to_string(),
-            ));
-        }
-
-        let text = String::from_utf8_lossy(&output.stdout);
+impl TemperatureSource for CpuSensor {
+    fn name(&self) -> &str {
+        "cpu"
+    }
 
-        // Look for the temperature in the output with various patterns
-        let patterns = [
-            r"Package id 0:\s*\+([0-9]+(?:\.[0-9]+)?)°C", // Intel
-            r"Tdie:\s*\+([0-9]+(?:\.[0-9]+)?)°C",         // AMD real die temp
-            r"Tctl:\s*\+([0-9]+(?:\.[0-9]+)?)°C",         // AMD control temp
-            r"temp1:\s*\+([0-9]+(?:\.[0-9]+)?)°C",        // fallback
-        ];
+    /// Check if the sensor is available
+    fn is_available(&self) -> bool {
+        HwmonSensor::new().is_available() || Self::is_sensors_available()
+    }
 
-        for pattern in patterns {
-            let re = Regex::new(pattern).map_err(|e| {
-                OcypusError::Sensor(format!(
-                    "Failed to compile regex pattern '{}': {}",
-                    pattern, e
-                ))
-            })?;
+    /// Get the CPU temperature using the best available method
+    fn read(&self) -> Result<f32> {
+        // Prefer reading sysfs directly, fall back to lm-sensors
+        HwmonSensor::new().get_cpu_temperature().or_else(|e| {
+            debug!("hwmon CPU temperature unavailable: {}", e);
+            Self::try_sensors().map(|temp| temp.value)
+        })
+    }
 
-            if let Some(captures) = re.captures(&text) {
-                let temp_str = captures
-                    .get(1)
-                    .ok_or_else(|| {
-                        OcypusError::Sensor("Failed to capture temperature".to_string())
-                    })?
-                    .as_str();
+    fn discover(&self) -> Vec<SensorReading> {
+        let package = HwmonSensor::new().find_cpu_package().ok();
+        let mut readings: Vec<SensorReading> = package
+            .iter()
+            .map(|t| SensorReading {
+                sensor: "cpu".to_string(),
+                ..t.to_reading(true, false)
+            })
+            .collect();
 
-                return temp_str.parse::<f32>().map_err(|e| {
-                    OcypusError::TemperatureParse(format!(
-                        "Failed to parse temperature '{}': {}",
-                        temp_str, e
-                    ))
-                });
-            }
+        if let Ok(temp) = Self::try_sensors() {
+            readings.push(SensorReading {
+                sensor: "cpu".to_string(),
+                ..temp.to_reading(package.is_none())
+            });
         }
 
-        Err(OcypusError::Sensor(
-            "CPU temperature not found in sensors output".to_string(),
-        ))
+        readings
+    }
+}
+
+impl CpuSensor {
+    /// Try the CPU chip ranked highest in lm-sensors JSON output
+    fn try_sensors() -> Result<SensorsTemp> {
+        let temps = lm_sensors::read_sensors()?;
+        LmSensorsSensor::new().find(&temps).cloned()
     }
 
-    /// Check if the sensor is available
-    pub fn is_available() -> bool {
+    /// Check if the lm-sensors command is available
+    fn is_sensors_available() -> bool {
         Command::new("sensors")
             .output()
             .map(|output| output.status.success())
@@ -74,15 +71,15 @@ mod tests {
     #[test]
     fn test_cpu_sensor_availability() {
         // This test will pass if 'sensors' command is available, fail otherwise
-        let available = CpuSensor::is_available();
+        let available = CpuSensor.is_available();
         assert!(available, "sensors command not available");
     }
 
     #[test]
     fn test_get_cpu_temperature() {
         // This test will only pass if 'sensors' command is available and returns valid data
-        if CpuSensor::is_available() {
-            let temp = CpuSensor::get_temperature();
+        if CpuSensor.is_available() {
+            let temp = CpuSensor.read();
             assert!(temp.is_ok(), "Failed to get CPU temperature: {:?}", temp);
 
             if let Ok(temp) = temp {
@@ -0,0 +1,180 @@
+//! GPU temperatures read straight from the hwmon directory of each DRM card
+//! (`/sys/class/drm/cardN/device/hwmon/hwmonM`), without any vendor tool.
+
+use crate::error::Result;
+use crate::sensor::gpu_sensor::{GpuTemp, GpuTempKind};
+use crate::sensor::hwmon::{channels, read_millidegrees, sorted_entries};
+use log::debug;
+use std::fs;
+use std::path::Path;
+
+/// Default sysfs DRM class directory
+pub const DEFAULT_DRM_ROOT: &str = "/sys/class/drm";
+
+/// Kernel drivers whose cards are read
+const DRM_DRIVERS: &[&str] = &["amdgpu", "i915", "xe"];
+
+/// Value of `key` in a sysfs `uevent` file (`KEY=value` lines)
+fn uevent_field(uevent: &str, key: &str) -> Option<String> {
+    uevent.lines().find_map(|line| {
+        let (k, v) = line.split_once('=')?;
+        (k == key).then(|| v.trim().to_string())
+    })
+}
+
+/// List the cards under `root` driven by amdgpu, i915 or xe, numbered as
+/// `cardN`. Labelled channels map onto their kind (amdgpu `edge`,
+/// `junction`, `mem`; xe `vram`) and other labels, such as xe `pkg`, are
+/// skipped; an unlabelled channel counts as the edge temperature. Cards
+/// without any temperature are left out.
+pub fn drm_gpus(root: &Path) -> Result<Vec<GpuTemp>> {
+    let mut gpus = Vec::new();
+
+    for card in sorted_entries(root, "card")? {
+        // Skip connectors such as `card0-DP-1`
+        let name = card.file_name().unwrap_or_default().to_string_lossy();
+        let Some(Ok(index)) = name.strip_prefix("card").map(str::parse::<u32>) else {
+            continue;
+        };
+
+        let device = card.join("device");
+        let Ok(uevent) = fs::read_to_string(device.join("uevent")) else {
+            continue;
+        };
+        let is_known = uevent_field(&uevent, "DRIVER")
+            .is_some_and(|driver| DRM_DRIVERS.contains(&driver.as_str()));
+        if !is_known {
+            continue;
+        }
+
+        let mut gpu = GpuTemp {
+            index,
+            pci: uevent_field(&uevent, "PCI_SLOT_NAME").and_then(|slot| slot.parse().ok()),
+            ..GpuTemp::default()
+        };
+        let hwmon = device.join("hwmon");
+        if hwmon.is_dir() {
+            // One card's unreadable hwmon directory leaves out only that card
+            let temps = match channels(&hwmon, "temp") {
+                Ok(temps) => temps,
+                Err(e) => {
+                    debug!("Skipping {}: {}", name, e);
+                    continue;
+                }
+            };
+            for (_, label, input) in temps {
+                let kind = match &label {
+                    Some(label) => GpuTempKind::from_label(label),
+                    None => Some(GpuTempKind::Edge),
+                };
+                if let (Some(kind), Ok(value)) = (kind, read_millidegrees(&input)) {
+                    gpu.set(kind, value);
+                }
+            }
+        }
+        if gpu.has_temperature() {
+            gpus.push(gpu);
+        }
+    }
+
+    Ok(gpus)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sensor::hwmon::tests::add_chip;
+    use tempfile::TempDir;
+
+    /// Add `cardN` with its PCI device and one hwmon chip to a fake sysfs tree
+    fn add_card(
+        root: &Path,
+        card: &str,
+        driver: &str,
+        slot: &str,
+        temps: &[(u32, Option<&str>, &str)],
+    ) {
+        let uevent = format!(
+            "DRIVER={}\nPCI_CLASS=30000\nPCI_SLOT_NAME={}\n",
+            driver, slot
+        );
+        let hwmon = format!("{}/device/hwmon/hwmon4", card);
+        add_chip(root, &hwmon, driver, "temp", temps, Some(&uevent));
+    }
+
+    #[test]
+    fn test_drm_gpus() {
+        let root = TempDir::new().unwrap();
+        add_card(
+            root.path(),
+            "card1",
+            "amdgpu",
+            "0000:03:00.0",
+            &[
+                (1, Some("edge\n"), "45000\n"),
+                (2, Some("junction\n"), "52000\n"),
+                (3, Some("mem\n"), "60000\n"),
+            ],
+        );
+        add_card(
+            root.path(),
+            "card0",
+            "xe",
+            "0000:00:02.0",
+            &[
+                // `pkg` and `mctrl` match no kind
+                (2, Some("pkg\n"), "48000\n"),
+                (3, Some("vram\n"), "51000\n"),
+                (4, Some("mctrl\n"), "47000\n"),
+            ],
+        );
+        // Connectors and cards of other drivers are skipped
+        fs::create_dir_all(root.path().join("card1-DP-1")).unwrap();
+        add_card(
+            root.path(),
+            "card2",
+            "nouveau",
+            "0000:0c:00.0",
+            &[(1, None, "50000\n")],
+        );
+
+        let gpus = drm_gpus(root.path()).unwrap();
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(
+            gpus[0],
+            GpuTemp {
+                index: 0,
+                pci: Some("0000:00:02.0".parse().unwrap()),
+                memory: Some(51.0),
+                ..GpuTemp::default()
+            }
+        );
+        assert_eq!(gpus[1].index, 1);
+        assert_eq!(gpus[1].selector(), "0000:03:00.0");
+        assert_eq!(
+            (gpus[1].edge, gpus[1].junction, gpus[1].memory),
+            (Some(45.0), Some(52.0), Some(60.0))
+        );
+    }
+
+    #[test]
+    fn test_drm_gpus_without_temperature() {
+        let root = TempDir::new().unwrap();
+        // Integrated i915 GPUs share the CPU package and expose no
+        // temperature of their own
+        add_card(root.path(), "card0", "i915", "0000:00:02.0", &[]);
+        add_card(
+            root.path(),
+            "card1",
+            "amdgpu",
+            "0000:03:00.0",
+            &[(1, None, "39000\n")],
+        );
+
+        let gpus = drm_gpus(root.path()).unwrap();
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].edge, Some(39.0));
+
+        assert!(drm_gpus(&root.path().join("missing")).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+use crate::error::{OcypusError, Result};
+use crate::sensor::gpu_sensor::run_tool as run;
+use crate::sensor::rocm;
+use crate::sensor::{MetricUnit, SensorReading, TemperatureSource};
+
+/// A non-temperature GPU metric
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GpuMetric {
+    /// Graphics engine utilisation in percent
+    Utilization,
+    /// Board power draw in watts
+    Power,
+}
+
+impl GpuMetric {
+    /// Unit the metric is reported in
+    pub fn unit(self) -> MetricUnit {
+        match self {
+            GpuMetric::Utilization => MetricUnit::Percent,
+            GpuMetric::Power => MetricUnit::Watts,
+        }
+    }
+}
+
+/// GPU utilisation or power draw from the vendor tools
+#[derive(Debug, Clone)]
+pub struct GpuMetricSensor {
+    metric: GpuMetric,
+}
+
+impl GpuMetricSensor {
+    pub fn new(metric: GpuMetric) -> Self {
+        Self { metric }
+    }
+
+    /// Backends in order of preference, with the name shown by `sensors`
+    fn backends(&self) -> [(&'static str, Result<f32>); 3] {
+        [
+            ("nvidia-smi", self.try_nvidia_smi()),
+            ("amd-smi", self.try_amd_smi()),
+            ("rocm-smi", self.try_rocm_smi()),
+        ]
+    }
+
+    fn try_nvidia_smi(&self) -> Result<f32> {
+        let query = match self.metric {
+            GpuMetric::Utilization => "--query-gpu=utilization.gpu",
+            GpuMetric::Power => "--query-gpu=power.draw",
+        };
+        let text = run("nvidia-smi", &[query, "--format=csv,noheader,nounits"])?;
+        parse_nvidia_smi(&text)
+    }
+
+    fn try_amd_smi(&self) -> Result<f32> {
+        let flag = match self.metric {
+            GpuMetric::Utilization => "--usage",
+            GpuMetric::Power => "--power",
+        };
+        let text = run("amd-smi", &["metric", flag, "--json"])?;
+        rocm::parse_amd_smi_metric(&text, self.metric)
+    }
+
+    fn try_rocm_smi(&self) -> Result<f32> {
+        let flag = match self.metric {
+            GpuMetric::Utilization => "--showuse",
+            GpuMetric::Power => "--showpower",
+        };
+        let text = run("rocm-smi", &[flag, "--json"])?;
+        rocm::parse_rocm_smi_metric(&text, self.metric)
+    }
+}
+
+impl TemperatureSource for GpuMetricSensor {
+    fn name(&self) -> &str {
+        match self.metric {
+            GpuMetric::Utilization => "gpu-load",
+            GpuMetric::Power => "gpu-power",
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        self.read().is_ok()
+    }
+
+    /// Get the metric from the first tool that reports it
+    fn read(&self) -> Result<f32> {
+        self.try_nvidia_smi()
+            .or_else(|_| self.try_amd_smi())
+            .or_else(|_| self.try_rocm_smi())
+    }
+
+    fn unit(&self) -> MetricUnit {
+        self.metric.unit()
+    }
+
+    fn discover(&self) -> Vec<SensorReading> {
+        let mut readings: Vec<SensorReading> = Vec::new();
+        for (backend, value) in self.backends() {
+            if let Ok(value) = value {
+                readings.push(SensorReading {
+                    sensor: self.name().to_string(),
+                    backend: backend.to_string(),
+                    chip: "gpu".to_string(),
+                    label: self.metric.unit().to_string(),
+                    value: Some(value),
+                    unit: self.metric.unit(),
+                    // The first working backend is the one `read` uses
+                    default: readings.is_empty(),
+                });
+            }
+        }
+        readings
+    }
+}
+
+/// Parse `nvidia-smi --format=csv,noheader,nounits` output for the first GPU
+pub fn parse_nvidia_smi(text: &str) -> Result<f32> {
+    let line = text
+        .lines()
+        .next()
+        .ok_or_else(|| OcypusError::Sensor("No output from nvidia-smi".to_string()))?
+        .trim();
+
+    // Unsupported fields read `[N/A]` or `[Not Supported]`
+    line.parse::<f32>()
+        .map_err(|_| OcypusError::Sensor(format!("nvidia-smi reported '{}'", line)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_nvidia_smi() {
+        assert_eq!(parse_nvidia_smi("37\n").unwrap(), 37.0);
+        assert_eq!(parse_nvidia_smi("112.45\n48.10\n").unwrap(), 112.45);
+        assert!(parse_nvidia_smi("[N/A]\n").is_err());
+        assert!(parse_nvidia_smi("").is_err());
+    }
+
+    #[test]
+    fn test_metric_units() {
+        let load = GpuMetricSensor::new(GpuMetric::Utilization);
+        assert_eq!(load.name(), "gpu-load");
+        assert_eq!(load.unit(), MetricUnit::Percent);
+        let power = GpuMetricSensor::new(GpuMetric::Power);
+        assert_eq!(power.name(), "gpu-power");
+        assert_eq!(power.unit(), MetricUnit::Watts);
+    }
+}
@@ -1,187 +1,602 @@
 use crate::error::{OcypusError, Result};
+use crate::sensor::drm;
+use crate::sensor::lm_sensors::{self, SensorsTemp};
+use crate::sensor::rocm;
+use crate::sensor::{MetricUnit, SensorReading, TemperatureSource};
+use log::debug;
+use std::fmt;
+use std::path::Path;
 use std::process::Command;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
 
-/// GPU temperature sensor
-pub struct GpuSensor;
+/// A GPU backend listing every card it sees
+type Backend = fn() -> Result<Vec<GpuTemp>>;
 
-impl GpuSensor {
-    /// Get the GPU temperature using the best available method
-    pub fn get_temperature() -> Result<f32> {
-        // Try different GPU monitoring tools in order of preference
-        Self::try_nvidia_smi()
-            .or_else(|_| Self::try_amd_smi())
-            .or_else(|_| Self::try_rocm_smi())
-            .or_else(|_| Self::try_sensors())
-    }
-
-    /// Try NVIDIA GPU temperature
-    fn try_nvidia_smi() -> Result<f32> {
-        let output = Command::new("nvidia-smi")
-            .args(&[
-                "--query-gpu=temperature.gpu",
-                "--format=csv,noheader,nounits",
-            ])
-            .output()
-            .map_err(|_| OcypusError::Sensor("nvidia-smi not available".to_string()))?;
-
-        if !output.status.success() {
-            return Err(OcypusError::Sensor("nvidia-smi command failed".to_string()));
-        }
-
-        let text = String::from_utf8_lossy(&output.stdout);
-        let temp_str = text
-            .lines()
-            .next()
-            .ok_or_else(|| OcypusError::Sensor("No output from nvidia-smi".to_string()))?
-            .trim();
-
-        temp_str.parse::<f32>().map_err(|e| {
-            OcypusError::TemperatureParse(format!(
-                "Failed to parse nvidia-smi temperature '{}': {}",
-                temp_str, e
-            ))
+/// Backends in order of preference, with the name shown by `sensors`
+const BACKENDS: [(&str, Backend); 5] = [
+    ("nvidia-smi", GpuSensor::try_nvidia_smi),
+    ("amd-smi", GpuSensor::try_amd_smi),
+    ("rocm-smi", GpuSensor::try_rocm_smi),
+    ("drm", GpuSensor::try_drm),
+    ("lm-sensors", GpuSensor::try_sensors),
+];
+
+/// lm-sensors drivers of GPUs
+const GPU_CHIPS: &[&str] = &["amdgpu", "radeon", "nouveau"];
+
+/// PCI address of a card, `domain:bus:device.function`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PciAddress {
+    pub domain: u32,
+    pub bus: u8,
+    pub device: u8,
+    pub function: u8,
+}
+
+impl PciAddress {
+    /// Address encoded in an lm-sensors chip name such as `amdgpu-pci-0300`
+    /// (bus, then device and function packed into one byte). lm-sensors
+    /// leaves out the domain, so it is taken as 0.
+    pub fn from_sensors_chip(chip: &str) -> Option<Self> {
+        let (_, addr) = chip.split_once("-pci-")?;
+        let addr = u16::from_str_radix(addr, 16).ok()?;
+        let [bus, devfn] = addr.to_be_bytes();
+        Some(Self {
+            domain: 0,
+            bus,
+            device: devfn >> 3,
+            function: devfn & 0x7,
         })
     }
+}
 
-    /// Try AMD GPU temperature (new ROCm)
-    fn try_amd_smi() -> Result<f32> {
-        let output = Command::new("amd-smi")
-            .args(&["metric", "--temperature"])
-            .output()
-            .map_err(|_| OcypusError::Sensor("amd-smi not available".to_string()))?;
+impl FromStr for PciAddress {
+    type Err = OcypusError;
 
-        if !output.status.success() {
-            return Err(OcypusError::Sensor("amd-smi command failed".to_string()));
+    /// Parse `[domain:]bus:device.function` in hex, e.g. `0000:03:00.0` or
+    /// nvidia-smi's `00000000:03:00.0`
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || OcypusError::Config(format!("Invalid PCI bus id: '{}'", s));
+        let (rest, function) = s.trim().rsplit_once('.').ok_or_else(invalid)?;
+        let mut parts = rest.rsplitn(3, ':');
+        let device = parts.next().unwrap_or_default();
+        let bus = parts.next().ok_or_else(invalid)?;
+        let domain = parts.next().unwrap_or("0");
+
+        let hex = |part: &str| u32::from_str_radix(part, 16).map_err(|_| invalid());
+        let (domain, bus, device, function) =
+            (hex(domain)?, hex(bus)?, hex(device)?, hex(function)?);
+        if bus > 0xff || device > 0x1f || function > 0x7 {
+            return Err(invalid());
         }
 
-        let text = String::from_utf8_lossy(&output.stdout);
+        Ok(Self {
+            domain,
+            bus: bus as u8,
+            device: device as u8,
+            function: function as u8,
+        })
+    }
+}
+
+impl fmt::Display for PciAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:04x}:{:02x}:{:02x}.{:x}",
+            self.domain, self.bus, self.device, self.function
+        )
+    }
+}
+
+/// Which card the GPU source reads, written after `gpu:`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum GpuSelector {
+    /// The first card of the first working backend
+    #[default]
+    First,
+    /// A card by the index its backend gives it (e.g. `gpu:1`)
+    Index(u32),
+    /// A card by UUID or unique ID (e.g. `gpu:GPU-5e3c...`)
+    Uuid(String),
+    /// A card by PCI bus id (e.g. `gpu:0000:03:00.0`)
+    Pci(PciAddress),
+    /// The hottest card across all backends (`gpu:max`)
+    Max,
+}
 
-        for line in text.lines() {
-            if line.to_lowercase().contains("edge") {
-                if let Some(temp_str) = Self::extract_number(line) {
-                    return temp_str.parse::<f32>().map_err(|e| {
-                        OcypusError::TemperatureParse(format!(
-                            "Failed to parse amd-smi temperature '{}': {}",
-                            temp_str, e
-                        ))
-                    });
-                }
-            }
+impl GpuSelector {
+    /// Whether `gpu` is the card this selects; `Max` matches none
+    pub fn matches(&self, gpu: &GpuTemp) -> bool {
+        match self {
+            GpuSelector::First => true,
+            GpuSelector::Index(index) => gpu.index == *index,
+            GpuSelector::Uuid(uuid) => gpu
+                .uuid
+                .as_deref()
+                .is_some_and(|u| u.eq_ignore_ascii_case(uuid)),
+            GpuSelector::Pci(pci) => gpu.pci == Some(*pci),
+            GpuSelector::Max => false,
         }
+    }
+}
+
+impl FromStr for GpuSelector {
+    type Err = OcypusError;
 
-        Err(OcypusError::Sensor(
-            "No edge temperature found in amd-smi output".to_string(),
-        ))
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        if s.eq_ignore_ascii_case("max") {
+            Ok(GpuSelector::Max)
+        } else if let Ok(index) = s.parse::<u32>() {
+            Ok(GpuSelector::Index(index))
+        } else if let Ok(pci) = PciAddress::from_str(s) {
+            Ok(GpuSelector::Pci(pci))
+        } else if s.is_empty() {
+            Err(OcypusError::Config("Empty GPU selector".to_string()))
+        } else {
+            Ok(GpuSelector::Uuid(s.to_string()))
+        }
     }
+}
 
-    /// Try AMD GPU temperature (old ROCm)
-    fn try_rocm_smi() -> Result<f32> {
-        let output = Command::new("rocm-smi")
-            .args(&["--showtemp"])
-            .output()
-            .map_err(|_| OcypusError::Sensor("rocm-smi not available".to_string()))?;
+impl fmt::Display for GpuSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuSelector::First => f.write_str("first GPU"),
+            GpuSelector::Index(index) => write!(f, "GPU {}", index),
+            GpuSelector::Uuid(uuid) => write!(f, "GPU {}", uuid),
+            GpuSelector::Pci(pci) => write!(f, "GPU at {}", pci),
+            GpuSelector::Max => f.write_str("hottest GPU"),
+        }
+    }
+}
 
-        if !output.status.success() {
-            return Err(OcypusError::Sensor("rocm-smi command failed".to_string()));
+/// Which of a card's temperatures to report, written after the card as
+/// `gpu:<card>/<kind>` or on its own as `gpu:<kind>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GpuTempKind {
+    /// Edge temperature; the core temperature (`temperature.gpu`) on NVIDIA
+    #[default]
+    Edge,
+    /// Junction (hotspot) temperature, which AMD cards throttle on
+    Junction,
+    /// Memory temperature
+    Memory,
+}
+
+impl GpuTempKind {
+    /// All kinds, in the order they are listed
+    pub const ALL: [GpuTempKind; 3] = [
+        GpuTempKind::Edge,
+        GpuTempKind::Junction,
+        GpuTempKind::Memory,
+    ];
+
+    /// Name used to select the kind
+    pub fn name(self) -> &'static str {
+        match self {
+            GpuTempKind::Edge => "edge",
+            GpuTempKind::Junction => "junction",
+            GpuTempKind::Memory => "memory",
         }
+    }
 
-        let text = String::from_utf8_lossy(&output.stdout);
+    /// The kind a backend's sensor label refers to, e.g. amd-smi's `HOTSPOT`,
+    /// rocm-smi's `Temperature (Sensor memory) (C)`, the amdgpu hwmon labels
+    /// `edge`, `junction` and `mem` or the Intel xe label `vram`. The xe
+    /// `pkg` (package) label matches none of the three kinds and is left
+    /// unmapped.
+    pub fn from_label(label: &str) -> Option<Self> {
+        let label = label.to_lowercase();
+        if label.contains("edge") {
+            Some(GpuTempKind::Edge)
+        } else if label.contains("junction") || label.contains("hotspot") {
+            Some(GpuTempKind::Junction)
+        } else if label.contains("mem") || label.contains("vram") {
+            Some(GpuTempKind::Memory)
+        } else {
+            None
+        }
+    }
+}
 
-        for line in text.lines() {
-            if let Some(temp_str) = Self::extract_number(line) {
-                return temp_str.parse::<f32>().map_err(|e| {
-                    OcypusError::TemperatureParse(format!(
-                        "Failed to parse rocm-smi temperature '{}': {}",
-                        temp_str, e
-                    ))
-                });
-            }
+impl FromStr for GpuTempKind {
+    type Err = OcypusError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "edge" => Ok(GpuTempKind::Edge),
+            "junction" | "hotspot" => Ok(GpuTempKind::Junction),
+            "memory" | "mem" => Ok(GpuTempKind::Memory),
+            _ => Err(OcypusError::Config(format!(
+                "Invalid GPU temperature: '{}'. Use 'edge', 'junction' or 'memory'",
+                s
+            ))),
         }
+    }
+}
 
-        Err(OcypusError::Sensor(
-            "No temperature found in rocm-smi output".to_string(),
-        ))
+/// Parse a GPU selector, `[card][/kind]`, e.g. `1`, `junction` or
+/// `0000:03:00.0/memory`
+pub fn parse_selector(s: &str) -> Result<(GpuSelector, GpuTempKind)> {
+    if let Ok(kind) = GpuTempKind::from_str(s) {
+        return Ok((GpuSelector::First, kind));
+    }
+    match s.rsplit_once('/') {
+        Some((card, kind)) => Ok((GpuSelector::from_str(card)?, GpuTempKind::from_str(kind)?)),
+        None => Ok((GpuSelector::from_str(s)?, GpuTempKind::Edge)),
     }
+}
 
-    /// Try lm-sensors as fallback
-    fn try_sensors() -> Result<f32> {
-        let output = Command::new("sensors")
-            .output()
-            .map_err(|_| OcypusError::Sensor("sensors command not available".to_string()))?;
+/// One card's temperatures as reported by a backend
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GpuTemp {
+    /// Index the backend gives the card
+    pub index: u32,
+    /// UUID or unique ID, if the backend reports one
+    pub uuid: Option<String>,
+    /// PCI address, if the backend reports one
+    pub pci: Option<PciAddress>,
+    /// Edge temperature in °C
+    pub edge: Option<f32>,
+    /// Junction temperature in °C
+    pub junction: Option<f32>,
+    /// Memory temperature in °C
+    pub memory: Option<f32>,
+}
 
-        if !output.status.success() {
-            return Err(OcypusError::Sensor("sensors command failed".to_string()));
+impl GpuTemp {
+    /// The temperature of the given kind, if the backend reports it
+    pub fn temperature(&self, kind: GpuTempKind) -> Option<f32> {
+        match kind {
+            GpuTempKind::Edge => self.edge,
+            GpuTempKind::Junction => self.junction,
+            GpuTempKind::Memory => self.memory,
         }
+    }
 
-        let text = String::from_utf8_lossy(&output.stdout);
+    /// Record a temperature, keeping the first one seen of each kind
+    pub(crate) fn set(&mut self, kind: GpuTempKind, value: f32) {
+        let slot = match kind {
+            GpuTempKind::Edge => &mut self.edge,
+            GpuTempKind::Junction => &mut self.junction,
+            GpuTempKind::Memory => &mut self.memory,
+        };
+        slot.get_or_insert(value);
+    }
 
-        for line in text.lines() {
-            let lower_line = line.to_lowercase();
-            if lower_line.contains("gpu")
-                || lower_line.contains("edge")
-                || lower_line.contains("junction")
-            {
-                if let Some(temp_str) = Self::extract_number(line) {
-                    return temp_str.parse::<f32>().map_err(|e| {
-                        OcypusError::TemperatureParse(format!(
-                            "Failed to parse sensors temperature '{}': {}",
-                            temp_str, e
-                        ))
-                    });
+    /// Whether the backend reported any temperature for the card
+    pub(crate) fn has_temperature(&self) -> bool {
+        GpuTempKind::ALL
+            .iter()
+            .any(|kind| self.temperature(*kind).is_some())
+    }
+
+    /// Selector naming this card, in order of preference: its PCI bus id,
+    /// else its UUID, else its index. Indices are numbered per backend and
+    /// can shift when cards come and go, so they are only used when the
+    /// backend reports neither
+    pub fn selector(&self) -> String {
+        match (&self.pci, &self.uuid) {
+            (Some(pci), _) => pci.to_string(),
+            (None, Some(uuid)) => uuid.clone(),
+            (None, None) => self.index.to_string(),
+        }
+    }
+}
+
+/// Combine the cards of several backends, best backend first. A card seen by
+/// several backends is kept once, matched by PCI address; cards without an
+/// address are only taken from the first backend that reports any card.
+pub fn merge_gpus(lists: &[Vec<GpuTemp>]) -> Vec<GpuTemp> {
+    let mut merged: Vec<GpuTemp> = Vec::new();
+    for list in lists {
+        let first = merged.is_empty();
+        for gpu in list {
+            let new = match gpu.pci {
+                Some(pci) => !merged.iter().any(|g| g.pci == Some(pci)),
+                None => first,
+            };
+            if new {
+                merged.push(gpu.clone());
+            }
+        }
+    }
+    merged
+}
+
+/// GPU temperature sensor
+#[derive(Debug, Clone, Default)]
+pub struct GpuSensor {
+    selector: GpuSelector,
+    kind: GpuTempKind,
+}
+
+impl TemperatureSource for GpuSensor {
+    fn name(&self) -> &str {
+        "gpu"
+    }
+
+    /// Check if the selected GPU can be read
+    fn is_available(&self) -> bool {
+        self.read().is_ok()
+    }
+
+    /// Get the temperature of the selected GPU from the first backend that
+    /// sees it
+    fn read(&self) -> Result<f32> {
+        if self.selector == GpuSelector::Max {
+            let lists: Vec<Vec<GpuTemp>> = BACKENDS
+                .iter()
+                .filter_map(|(_, backend)| backend().ok())
+                .collect();
+            return merge_gpus(&lists)
+                .iter()
+                .filter_map(|gpu| gpu.temperature(self.kind))
+                .max_by(f32::total_cmp)
+                .ok_or_else(|| {
+                    OcypusError::Sensor(format!("No GPU {} temperature found", self.kind.name()))
+                });
+        }
+
+        // A backend may see the card but not report this kind; later ones
+        // may still have it
+        for (name, backend) in BACKENDS {
+            match backend() {
+                Ok(gpus) => {
+                    if let Some(value) = gpus
+                        .iter()
+                        .filter(|gpu| self.selector.matches(gpu))
+                        .find_map(|gpu| gpu.temperature(self.kind))
+                    {
+                        return Ok(value);
+                    }
                 }
+                Err(e) => debug!("{}: {}", name, e),
             }
         }
 
-        Err(OcypusError::Sensor(
-            "No GPU temperature found in sensors output".to_string(),
-        ))
+        Err(OcypusError::Sensor(format!(
+            "No {} temperature found for {}",
+            self.kind.name(),
+            self.selector
+        )))
     }
 
-    /// Extract the first floating-point number from a string
-    fn extract_number(s: &str) -> Option<String> {
-        let mut buf = String::new();
-        let mut seen_digit = false;
+    fn discover(&self) -> Vec<SensorReading> {
+        let lists: Vec<(&str, Vec<GpuTemp>)> = BACKENDS
+            .iter()
+            .filter_map(|(name, backend)| backend().ok().map(|gpus| (*name, gpus)))
+            .collect();
 
-        for c in s.chars() {
-            if c.is_ascii_digit() || (c == '.' && seen_digit) {
-                buf.push(c);
-                seen_digit = true;
-            } else if seen_digit {
-                break;
+        let mut readings: Vec<SensorReading> = Vec::new();
+        let mut found_default = false;
+        for (backend, gpus) in &lists {
+            for gpu in gpus {
+                for kind in GpuTempKind::ALL {
+                    let Some(value) = gpu.temperature(kind) else {
+                        continue;
+                    };
+                    // The first match is the one `read` uses
+                    let default = !found_default && kind == self.kind && self.selector.matches(gpu);
+                    found_default |= default;
+                    readings.push(SensorReading {
+                        sensor: selector_name(&gpu.selector(), kind),
+                        backend: backend.to_string(),
+                        chip: format!("gpu{}", gpu.index),
+                        label: kind.name().to_string(),
+                        value: Some(value),
+                        unit: MetricUnit::Celsius,
+                        default,
+                    });
+                }
             }
         }
 
-        if buf.is_empty() {
-            None
-        } else {
-            Some(buf)
+        let merged = merge_gpus(&lists.into_iter().map(|(_, gpus)| gpus).collect::<Vec<_>>());
+        if merged.len() > 1 {
+            for kind in GpuTempKind::ALL {
+                let Some(value) = merged
+                    .iter()
+                    .filter_map(|gpu| gpu.temperature(kind))
+                    .max_by(f32::total_cmp)
+                else {
+                    continue;
+                };
+                readings.push(SensorReading {
+                    sensor: selector_name("max", kind),
+                    backend: "-".to_string(),
+                    chip: format!("{} GPUs", merged.len()),
+                    label: format!("hottest {}", kind.name()),
+                    value: Some(value),
+                    unit: MetricUnit::Celsius,
+                    default: self.selector == GpuSelector::Max && kind == self.kind,
+                });
+            }
         }
+        readings
     }
 
-    /// Check if any GPU sensor is available
-    #[allow(unused)]
-    pub fn is_available() -> bool {
-        Self::try_nvidia_smi().is_ok()
-            || Self::try_amd_smi().is_ok()
-            || Self::try_rocm_smi().is_ok()
-            || Self::try_sensors().is_ok()
+    /// Select a card by index, UUID or PCI bus id, or `max` for the hottest,
+    /// optionally followed by the temperature kind (e.g. `1/junction`)
+    fn select(&self, selector: &str) -> Result<Arc<dyn TemperatureSource>> {
+        let (selector, kind) = parse_selector(selector)?;
+        Ok(Arc::new(Self::with_selector(selector).with_kind(kind)))
     }
 }
 
+impl GpuSensor {
+    /// Create a sensor reading the first GPU found
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a sensor reading the card picked by `selector`
+    pub fn with_selector(selector: GpuSelector) -> Self {
+        Self {
+            selector,
+            kind: GpuTempKind::default(),
+        }
+    }
+
+    /// Report the given kind of temperature instead of the edge temperature
+    pub fn with_kind(mut self, kind: GpuTempKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// NVIDIA cards
+    fn try_nvidia_smi() -> Result<Vec<GpuTemp>> {
+        let text = run_tool(
+            "nvidia-smi",
+            &[
+                "--query-gpu=index,uuid,pci.bus_id,temperature.gpu,temperature.memory",
+                "--format=csv,noheader,nounits",
+            ],
+        )?;
+        Ok(parse_nvidia_smi_gpus(&text))
+    }
+
+    /// AMD cards (new ROCm)
+    fn try_amd_smi() -> Result<Vec<GpuTemp>> {
+        let metric = run_tool("amd-smi", &["metric", "--temperature", "--json"])?;
+        rocm::parse_amd_smi_gpus(&metric, amd_smi_list())
+    }
+
+    /// AMD cards (old ROCm)
+    fn try_rocm_smi() -> Result<Vec<GpuTemp>> {
+        let text = run_tool(
+            "rocm-smi",
+            &["--showtemp", "--showbus", "--showuniqueid", "--json"],
+        )?;
+        rocm::parse_rocm_smi_gpus(&text)
+    }
+
+    /// amdgpu, i915 and xe cards from sysfs
+    fn try_drm() -> Result<Vec<GpuTemp>> {
+        drm::drm_gpus(Path::new(drm::DEFAULT_DRM_ROOT))
+    }
+
+    /// GPU chips in lm-sensors output as fallback
+    fn try_sensors() -> Result<Vec<GpuTemp>> {
+        Ok(sensors_gpus(&lm_sensors::read_sensors()?))
+    }
+}
+
+/// `--sensor` value selecting a card (or `max`) and kind; the edge kind is
+/// left implicit
+fn selector_name(card: &str, kind: GpuTempKind) -> String {
+    match kind {
+        GpuTempKind::Edge => format!("gpu:{}", card),
+        _ => format!("gpu:{}/{}", card, kind.name()),
+    }
+}
+
+/// `amd-smi list --json` output, which holds the bus ids and UUIDs missing
+/// from the metrics. They don't change while running, so the list is run
+/// once and kept; a failed run is retried on the next read.
+fn amd_smi_list() -> Option<&'static str> {
+    static LIST: OnceLock<String> = OnceLock::new();
+    if let Some(list) = LIST.get() {
+        return Some(list);
+    }
+    let list = run_tool("amd-smi", &["list", "--json"]).ok()?;
+    Some(LIST.get_or_init(|| list))
+}
+
+/// Run a vendor tool and return its standard output
+pub(crate) fn run_tool(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|_| OcypusError::Sensor(format!("{} not available", program)))?;
+
+    if !output.status.success() {
+        return Err(OcypusError::Sensor(format!("{} command failed", program)));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parse `nvidia-smi --query-gpu=index,uuid,pci.bus_id,temperature.gpu,
+/// temperature.memory --format=csv,noheader,nounits` output. nvidia-smi has
+/// no junction temperature, and reads `[N/A]` for memory on most consumer
+/// cards. Cards without any reading are left out.
+pub fn parse_nvidia_smi_gpus(text: &str) -> Vec<GpuTemp> {
+    text.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [index, uuid, bus, core, memory] = fields[..] else {
+                return None;
+            };
+            let gpu = GpuTemp {
+                index: index.parse().ok()?,
+                uuid: Some(uuid.to_string()),
+                pci: bus.parse().ok(),
+                edge: core.parse().ok(),
+                memory: memory.parse().ok(),
+                ..GpuTemp::default()
+            };
+            gpu.has_temperature().then_some(gpu)
+        })
+        .collect()
+}
+
+/// GPU chips among lm-sensors inputs, numbered in the order they are listed.
+/// The amdgpu features `edge`, `junction` and `mem` give their kinds; on
+/// other chips the first temperature counts as the edge temperature.
+pub fn sensors_gpus(temps: &[SensorsTemp]) -> Vec<GpuTemp> {
+    let mut chips: Vec<&str> = temps
+        .iter()
+        .filter(|t| GPU_CHIPS.contains(&t.driver()))
+        .map(|t| t.chip.as_str())
+        .collect();
+    chips.dedup();
+
+    chips
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, chip)| {
+            let mut gpu = GpuTemp {
+                index: index as u32,
+                pci: PciAddress::from_sensors_chip(chip),
+                ..GpuTemp::default()
+            };
+            for temp in temps.iter().filter(|t| t.chip == chip) {
+                let kind = GpuTempKind::from_label(&temp.feature).unwrap_or_default();
+                gpu.set(kind, temp.value);
+            }
+            gpu.has_temperature().then_some(gpu)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn gpu(index: u32, pci: Option<&str>, edge: f32) -> GpuTemp {
+        GpuTemp {
+            index,
+            pci: pci.map(|pci| pci.parse().unwrap()),
+            edge: Some(edge),
+            ..GpuTemp::default()
+        }
+    }
+
     #[test]
     fn test_gpu_sensor_availability() {
-        let available = GpuSensor::is_available();
+        let available = GpuSensor::new().is_available();
         assert!(available, "GPU sensor not available");
     }
 
     #[test]
     fn test_get_gpu_temperature() {
-        if GpuSensor::is_available() {
-            let temp = GpuSensor::get_temperature();
+        let sensor = GpuSensor::new();
+        if sensor.is_available() {
+            let temp = sensor.read();
             assert!(temp.is_ok(), "Failed to get GPU temperature: {:?}", temp);
 
             if let Ok(temp) = temp {
@@ -192,13 +607,138 @@ mod tests {
     }
 
     #[test]
-    fn test_extract_number() {
+    fn test_pci_address() {
+        let pci: PciAddress = "00000000:0A:00.0".parse().unwrap();
+        assert_eq!(pci.to_string(), "0000:0a:00.0");
+        assert_eq!("0a:00.0".parse::<PciAddress>().unwrap(), pci);
+        assert!("0000:03:00".parse::<PciAddress>().is_err());
+        assert!("0000:03:20.0".parse::<PciAddress>().is_err());
+
+        let chip = PciAddress::from_sensors_chip("amdgpu-pci-0a09").unwrap();
+        assert_eq!(chip.to_string(), "0000:0a:01.1");
+        assert_eq!(PciAddress::from_sensors_chip("k10temp-isa-0000"), None);
+    }
+
+    #[test]
+    fn test_selector() {
+        let parse = |s: &str| GpuSelector::from_str(s).unwrap();
+        assert_eq!(parse("1"), GpuSelector::Index(1));
+        assert_eq!(parse("MAX"), GpuSelector::Max);
+        assert_eq!(
+            parse("0000:03:00.0"),
+            GpuSelector::Pci("03:00.0".parse().unwrap())
+        );
+        assert_eq!(parse("GPU-5e3c"), GpuSelector::Uuid("GPU-5e3c".to_string()));
+
+        let card = GpuTemp {
+            uuid: Some("GPU-5E3C".to_string()),
+            ..gpu(1, Some("03:00.0"), 50.0)
+        };
+        assert!(parse("gpu-5e3c").matches(&card));
+        assert!(parse("1").matches(&card));
+        assert!(parse("3:0.0").matches(&card));
+        assert!(!parse("0").matches(&card));
+        assert!(!parse("max").matches(&card));
+
+        assert_eq!(card.selector(), "0000:03:00.0");
+        let card = GpuTemp { pci: None, ..card };
+        assert_eq!(card.selector(), "GPU-5E3C");
+        assert!(parse(&card.selector()).matches(&card));
+        let card = GpuTemp { uuid: None, ..card };
+        assert_eq!(card.selector(), "1");
+    }
+
+    #[test]
+    fn test_temperature_kind() {
+        assert_eq!(
+            parse_selector("junction").unwrap(),
+            (GpuSelector::First, GpuTempKind::Junction)
+        );
+        assert_eq!(
+            parse_selector("0000:03:00.0/mem").unwrap(),
+            (
+                GpuSelector::Pci("03:00.0".parse().unwrap()),
+                GpuTempKind::Memory
+            )
+        );
+        assert_eq!(
+            parse_selector("max/hotspot").unwrap(),
+            (GpuSelector::Max, GpuTempKind::Junction)
+        );
+        assert_eq!(
+            parse_selector("1").unwrap(),
+            (GpuSelector::Index(1), GpuTempKind::Edge)
+        );
+        assert!(parse_selector("1/vram").is_err());
+
+        assert_eq!(
+            GpuTempKind::from_label("HOTSPOT"),
+            Some(GpuTempKind::Junction)
+        );
+        assert_eq!(
+            GpuTempKind::from_label("Temperature (Sensor memory) (C)"),
+            Some(GpuTempKind::Memory)
+        );
+        assert_eq!(GpuTempKind::from_label("pkg"), None);
+        assert_eq!(GpuTempKind::from_label("vram"), Some(GpuTempKind::Memory));
+        assert_eq!(GpuTempKind::from_label("temp1"), None);
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi_gpus() {
+        let text = "\
+0, GPU-5e3c1d7a-1111-2222-3333-444455556666, 00000000:01:00.0, 45, [N/A]
+1, GPU-9b0f2e4c-aaaa-bbbb-cccc-ddddeeeeffff, 00000000:02:00.0, 61, 84
+2, GPU-00000000-0000-0000-0000-000000000000, 00000000:03:00.0, [N/A], [N/A]
+";
+        let gpus = parse_nvidia_smi_gpus(text);
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].memory, None);
+        assert_eq!(gpus[1].index, 1);
+        assert_eq!(gpus[1].pci.unwrap().to_string(), "0000:02:00.0");
+        assert_eq!(gpus[1].edge, Some(61.0));
+        assert_eq!(gpus[1].temperature(GpuTempKind::Memory), Some(84.0));
+        assert_eq!(gpus[1].junction, None);
+        assert!(gpus[0].uuid.as_deref().unwrap().starts_with("GPU-5e3c"));
+    }
+
+    #[test]
+    fn test_sensors_gpus() {
+        let json = r#"{
+           "amdgpu-pci-0300":{
+              "edge":{ "temp1_input": 40.0 },
+              "junction":{ "temp2_input": 44.0 },
+              "mem":{ "temp3_input": 50.0 }
+           },
+           "k10temp-pci-00c3":{ "Tctl":{ "temp1_input": 60.0 } },
+           "nouveau-pci-0c00":{ "temp1":{ "temp1_input": 55.0 } }
+        }"#;
+        let temps = lm_sensors::parse_sensors_json(json).unwrap();
+        let amd = GpuTemp {
+            junction: Some(44.0),
+            memory: Some(50.0),
+            ..gpu(0, Some("03:00.0"), 40.0)
+        };
         assert_eq!(
-            GpuSensor::extract_number("Temperature: 45.5°C"),
-            Some("45.5".to_string())
+            sensors_gpus(&temps),
+            vec![amd, gpu(1, Some("0c:00.0"), 55.0)]
         );
-        assert_eq!(GpuSensor::extract_number("45°C"), Some("45".to_string()));
-        assert_eq!(GpuSensor::extract_number("No numbers here"), None);
-        assert_eq!(GpuSensor::extract_number(""), None);
+    }
+
+    #[test]
+    fn test_merge_gpus() {
+        let nvidia = vec![gpu(0, Some("01:00.0"), 45.0)];
+        let rocm = vec![gpu(0, Some("03:00.0"), 52.0), gpu(1, None, 70.0)];
+        let sensors = vec![gpu(0, Some("01:00.0"), 44.0), gpu(1, Some("03:00.0"), 51.0)];
+
+        let merged = merge_gpus(&[nvidia.clone(), rocm, sensors]);
+        assert_eq!(
+            merged,
+            vec![gpu(0, Some("01:00.0"), 45.0), gpu(0, Some("03:00.0"), 52.0)]
+        );
+
+        // Cards without an address count when nothing came before
+        let merged = merge_gpus(&[Vec::new(), vec![gpu(0, None, 40.0)], nvidia]);
+        assert_eq!(merged.len(), 2);
     }
 }
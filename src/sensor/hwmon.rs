@@ -0,0 +1,796 @@
+use crate::error::{OcypusError, Result};
+use crate::sensor::{self, MetricUnit, SensorReading, TemperatureSource};
+use log::debug;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Default sysfs hwmon class directory
+pub const DEFAULT_HWMON_ROOT: &str = "/sys/class/hwmon";
+
+/// A single temperature channel exposed by a hwmon chip
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HwmonTemp {
+    /// Chip name from the `name` attribute (e.g. `coretemp`)
+    pub chip: String,
+    /// Channel label from `temp*_label`, if present
+    pub label: Option<String>,
+    /// Path to the `temp*_input` attribute
+    pub input: PathBuf,
+}
+
+impl HwmonTemp {
+    /// Label, or the attribute name (e.g. `temp1`) for unlabelled channels
+    pub fn display_label(&self) -> String {
+        self.label
+            .clone()
+            .unwrap_or_else(|| attribute_name(&self.input))
+    }
+
+    /// `chip/label` selector naming only this channel. The chip is given by
+    /// its hwmon directory (e.g. `hwmon3`) if `shared_name` says another
+    /// chip has the same name.
+    pub fn selector(&self, shared_name: bool) -> String {
+        format!(
+            "{}/{}",
+            chip_id(&self.chip, &self.input, shared_name),
+            self.display_label()
+        )
+    }
+
+    /// Discovery row for this channel
+    pub fn to_reading(&self, default: bool, shared_name: bool) -> SensorReading {
+        SensorReading {
+            sensor: format!("hwmon:{}", self.selector(shared_name)),
+            backend: "hwmon".to_string(),
+            chip: self.chip.clone(),
+            label: self.display_label(),
+            value: read_millidegrees(&self.input).ok(),
+            unit: MetricUnit::Celsius,
+            default,
+        }
+    }
+}
+
+/// Native sysfs hwmon temperature sensor
+#[derive(Debug, Clone)]
+pub struct HwmonSensor {
+    root: PathBuf,
+    /// Explicit `chip` or `chip/label` channel; the CPU package if `None`
+    channel: Option<String>,
+}
+
+impl Default for HwmonSensor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HwmonSensor {
+    /// Create a sensor reading from the system hwmon class directory
+    pub fn new() -> Self {
+        Self::with_root(DEFAULT_HWMON_ROOT)
+    }
+
+    /// Create a sensor reading from a custom hwmon root
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            channel: None,
+        }
+    }
+
+    /// Narrow the sensor to a `chip` or `chip/label` channel
+    pub fn with_channel(mut self, channel: impl Into<String>) -> Self {
+        self.channel = Some(channel.into());
+        self
+    }
+
+    /// List every temperature channel of every hwmon chip
+    pub fn temperatures(&self) -> Result<Vec<HwmonTemp>> {
+        Ok(channels(&self.root, "temp")?
+            .into_iter()
+            .map(|(chip, label, input)| HwmonTemp { chip, label, input })
+            .collect())
+    }
+
+    /// Find the CPU package temperature channel
+    pub fn find_cpu_package(&self) -> Result<HwmonTemp> {
+        let temps = self.temperatures()?;
+        sensor::find_cpu(&temps, |t| &t.chip, |t| t.label.as_deref())
+            .cloned()
+            .ok_or_else(|| {
+                OcypusError::Sensor(format!(
+                    "No CPU temperature found under {}",
+                    self.root.display()
+                ))
+            })
+    }
+
+    /// Find the channel matching the configured `chip` or `chip/label`. The
+    /// chip may be given by name or hwmon directory (`hwmon3`), and
+    /// unlabelled channels are matched by attribute name (`nvme/temp2`).
+    pub fn find_channel(&self, channel: &str) -> Result<HwmonTemp> {
+        let (chip, label) = match channel.split_once('/') {
+            Some((chip, label)) => (chip, Some(label)),
+            None => (channel, None),
+        };
+
+        self.temperatures()?
+            .into_iter()
+            .find(|t| {
+                is_chip(&t.chip, &t.input, chip) && label.is_none_or(|l| t.display_label() == l)
+            })
+            .ok_or_else(|| {
+                OcypusError::Sensor(format!(
+                    "Hwmon channel '{}' not found under {}",
+                    channel,
+                    self.root.display()
+                ))
+            })
+    }
+
+    /// Get the CPU package temperature in °C
+    pub fn get_cpu_temperature(&self) -> Result<f32> {
+        let temp = self.find_cpu_package()?;
+        read_millidegrees(&temp.input)
+    }
+
+    /// Find the channel this sensor reads
+    fn find(&self) -> Result<HwmonTemp> {
+        match &self.channel {
+            Some(channel) => self.find_channel(channel),
+            None => self.find_cpu_package(),
+        }
+    }
+}
+
+impl TemperatureSource for HwmonSensor {
+    fn name(&self) -> &str {
+        "hwmon"
+    }
+
+    /// Check if the configured channel can be found
+    fn is_available(&self) -> bool {
+        self.find().is_ok()
+    }
+
+    fn read(&self) -> Result<f32> {
+        read_millidegrees(&self.find()?.input)
+    }
+
+    fn discover(&self) -> Vec<SensorReading> {
+        let selected = self.find().ok();
+        let temps = self.temperatures().unwrap_or_default();
+        let shared = shared_names(temps.iter().map(|t| (t.chip.as_str(), t.input.as_path())));
+        temps
+            .iter()
+            .map(|t| t.to_reading(Some(t) == selected.as_ref(), shared.contains(&t.chip)))
+            .collect()
+    }
+
+    fn select(&self, selector: &str) -> Result<Arc<dyn TemperatureSource>> {
+        Ok(Arc::new(self.clone().with_channel(selector)))
+    }
+}
+
+/// A fan tachometer channel exposed by a hwmon chip
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HwmonFan {
+    /// Chip name from the `name` attribute (e.g. `nct6798`)
+    pub chip: String,
+    /// Channel label from `fan*_label`, if present
+    pub label: Option<String>,
+    /// Path to the `fan*_input` attribute
+    pub input: PathBuf,
+}
+
+impl HwmonFan {
+    /// Label, or the attribute name (e.g. `fan2`) for unlabelled channels
+    pub fn display_label(&self) -> String {
+        self.label
+            .clone()
+            .unwrap_or_else(|| attribute_name(&self.input))
+    }
+
+    /// Discovery row for this channel; see `HwmonTemp::selector` for
+    /// `shared_name`
+    pub fn to_reading(&self, default: bool, shared_name: bool) -> SensorReading {
+        let chip = chip_id(&self.chip, &self.input, shared_name);
+        SensorReading {
+            sensor: format!("fan:{}/{}", chip, self.display_label()),
+            backend: "hwmon".to_string(),
+            chip: self.chip.clone(),
+            label: self.display_label(),
+            value: read_rpm(&self.input).ok(),
+            unit: MetricUnit::Rpm,
+            default,
+        }
+    }
+}
+
+/// Fan speed from hwmon `fan*_input` attributes
+#[derive(Debug, Clone)]
+pub struct FanSensor {
+    root: PathBuf,
+    /// Explicit `chip` or `chip/label` channel; the first spinning fan if `None`
+    channel: Option<String>,
+}
+
+impl Default for FanSensor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FanSensor {
+    /// Create a sensor reading from the system hwmon class directory
+    pub fn new() -> Self {
+        Self::with_root(DEFAULT_HWMON_ROOT)
+    }
+
+    /// Create a sensor reading from a custom hwmon root
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            channel: None,
+        }
+    }
+
+    /// Narrow the sensor to a `chip` or `chip/label` channel. Unlabelled
+    /// channels are matched by attribute name (e.g. `nct6798/fan2`).
+    pub fn with_channel(mut self, channel: impl Into<String>) -> Self {
+        self.channel = Some(channel.into());
+        self
+    }
+
+    /// List every fan channel of every hwmon chip
+    pub fn fans(&self) -> Result<Vec<HwmonFan>> {
+        Ok(channels(&self.root, "fan")?
+            .into_iter()
+            .map(|(chip, label, input)| HwmonFan { chip, label, input })
+            .collect())
+    }
+
+    /// Find the channel this sensor reads
+    fn find(&self) -> Result<HwmonFan> {
+        let fans = self.fans()?;
+
+        let found = match &self.channel {
+            Some(channel) => {
+                let (chip, label) = match channel.split_once('/') {
+                    Some((chip, label)) => (chip, Some(label)),
+                    None => (channel.as_str(), None),
+                };
+                fans.into_iter().find(|f| {
+                    is_chip(&f.chip, &f.input, chip) && label.is_none_or(|l| f.display_label() == l)
+                })
+            }
+            // Headers without a fan attached read 0; skip them if we can
+            None => fans
+                .iter()
+                .find(|f| read_rpm(&f.input).is_ok_and(|rpm| rpm > 0.0))
+                .or_else(|| fans.first())
+                .cloned(),
+        };
+
+        found.ok_or_else(|| {
+            OcypusError::Sensor(format!(
+                "Fan '{}' not found under {}",
+                self.channel.as_deref().unwrap_or("any"),
+                self.root.display()
+            ))
+        })
+    }
+}
+
+impl TemperatureSource for FanSensor {
+    fn name(&self) -> &str {
+        "fan"
+    }
+
+    /// Check if the configured fan can be found
+    fn is_available(&self) -> bool {
+        self.find().is_ok()
+    }
+
+    /// Get the fan speed in RPM
+    fn read(&self) -> Result<f32> {
+        read_rpm(&self.find()?.input)
+    }
+
+    fn unit(&self) -> MetricUnit {
+        MetricUnit::Rpm
+    }
+
+    fn discover(&self) -> Vec<SensorReading> {
+        let selected = self.find().ok();
+        let fans = self.fans().unwrap_or_default();
+        let shared = shared_names(fans.iter().map(|f| (f.chip.as_str(), f.input.as_path())));
+        fans.iter()
+            .map(|f| f.to_reading(Some(f) == selected.as_ref(), shared.contains(&f.chip)))
+            .collect()
+    }
+
+    fn select(&self, selector: &str) -> Result<Arc<dyn TemperatureSource>> {
+        Ok(Arc::new(self.clone().with_channel(selector)))
+    }
+}
+
+/// Read a sysfs fan attribute in RPM
+pub fn read_rpm(path: &Path) -> Result<f32> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| OcypusError::Sensor(format!("Failed to read {}: {}", path.display(), e)))?;
+
+    let rpm = text.trim().parse::<u32>().map_err(|e| {
+        OcypusError::Sensor(format!(
+            "Failed to parse '{}' from {}: {}",
+            text.trim(),
+            path.display(),
+            e
+        ))
+    })?;
+
+    Ok(rpm as f32)
+}
+
+/// List `(chip, label, input)` for every `<prefix>*_input` attribute of
+/// every hwmon chip under `root`
+pub(crate) fn channels(
+    root: &Path,
+    prefix: &str,
+) -> Result<Vec<(String, Option<String>, PathBuf)>> {
+    let mut channels = Vec::new();
+
+    for chip_dir in sorted_entries(root, "hwmon")? {
+        let chip = match fs::read_to_string(chip_dir.join("name")) {
+            Ok(name) => name.trim().to_string(),
+            Err(_) => continue,
+        };
+
+        // A chip that was unplugged or can't be listed shouldn't hide the rest
+        let inputs = match sorted_entries(&chip_dir, prefix) {
+            Ok(inputs) => inputs,
+            Err(e) => {
+                debug!("Skipping {}: {}", chip_dir.display(), e);
+                continue;
+            }
+        };
+
+        for input in inputs {
+            let file_name = input.file_name().unwrap_or_default().to_string_lossy();
+            let Some(attribute) = file_name.strip_suffix("_input") else {
+                continue;
+            };
+
+            let label = fs::read_to_string(chip_dir.join(format!("{}_label", attribute)))
+                .ok()
+                .map(|l| l.trim().to_string());
+
+            channels.push((chip.clone(), label, input));
+        }
+    }
+
+    Ok(channels)
+}
+
+/// Hwmon directory of the chip an attribute belongs to (e.g. `hwmon3`)
+fn chip_dir(input: &Path) -> String {
+    input
+        .parent()
+        .and_then(Path::file_name)
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Whether a selector's chip part names the chip of an attribute, by chip
+/// name or hwmon directory
+fn is_chip(name: &str, input: &Path, chip: &str) -> bool {
+    name == chip || chip_dir(input) == chip
+}
+
+/// Chip part of a selector: the chip name, or the hwmon directory if the
+/// name is shared with another chip
+fn chip_id(name: &str, input: &Path, shared_name: bool) -> String {
+    if shared_name {
+        chip_dir(input)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Chip names used by more than one hwmon directory, given the chip name
+/// and an attribute path of each channel
+fn shared_names<'a>(channels: impl Iterator<Item = (&'a str, &'a Path)>) -> Vec<String> {
+    let mut dirs: Vec<(&str, String)> = channels
+        .map(|(name, input)| (name, chip_dir(input)))
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+
+    let mut shared: Vec<String> = dirs
+        .windows(2)
+        .filter(|pair| pair[0].0 == pair[1].0)
+        .map(|pair| pair[0].0.to_string())
+        .collect();
+    shared.dedup();
+    shared
+}
+
+/// Attribute name of an `*_input` path (e.g. `temp1`)
+fn attribute_name(input: &Path) -> String {
+    input
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .trim_end_matches("_input")
+        .to_string()
+}
+
+/// Read a sysfs millidegree attribute and convert it to °C
+pub fn read_millidegrees(path: &Path) -> Result<f32> {
+    let text = fs::read_to_string(path)
+        .map_err(|e| OcypusError::Sensor(format!("Failed to read {}: {}", path.display(), e)))?;
+
+    let millidegrees = text.trim().parse::<i64>().map_err(|e| {
+        OcypusError::TemperatureParse(format!(
+            "Failed to parse '{}' from {}: {}",
+            text.trim(),
+            path.display(),
+            e
+        ))
+    })?;
+
+    Ok(millidegrees as f32 / 1000.0)
+}
+
+/// List entries of `dir` whose names start with `prefix`, in natural order
+/// (so `hwmon10` sorts after `hwmon2`)
+pub(crate) fn sorted_entries(dir: &Path, prefix: &str) -> Result<Vec<PathBuf>> {
+    let mut entries: Vec<(u32, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let rest = name.strip_prefix(prefix)?;
+            let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+            let index = digits.parse::<u32>().ok()?;
+            Some((index, entry.path()))
+        })
+        .collect();
+
+    entries.sort();
+    Ok(entries.into_iter().map(|(_, path)| path).collect())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Add a hwmon chip directory with `<prefix>N_input` attributes and
+    /// their labels. A `uevent` is written for the device the chip belongs
+    /// to, two levels up as in `<device>/hwmon/hwmonN`.
+    pub(crate) fn add_chip(
+        root: &Path,
+        dir: &str,
+        name: &str,
+        prefix: &str,
+        channels: &[(u32, Option<&str>, &str)],
+        uevent: Option<&str>,
+    ) {
+        let chip = root.join(dir);
+        fs::create_dir_all(&chip).unwrap();
+        fs::write(chip.join("name"), format!("{}\n", name)).unwrap();
+
+        for (index, label, value) in channels {
+            fs::write(chip.join(format!("{}{}_input", prefix, index)), value).unwrap();
+            if let Some(label) = label {
+                fs::write(chip.join(format!("{}{}_label", prefix, index)), label).unwrap();
+            }
+        }
+
+        if let Some(uevent) = uevent {
+            let device = chip.parent().and_then(Path::parent).unwrap();
+            fs::write(device.join("uevent"), uevent).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_intel_package_temperature() {
+        let root = TempDir::new().unwrap();
+        add_chip(
+            root.path(),
+            "hwmon0",
+            "acpitz",
+            "temp",
+            &[(1, None, "27800\n")],
+            None,
+        );
+        add_chip(
+            root.path(),
+            "hwmon3",
+            "coretemp",
+            "temp",
+            &[
+                (1, Some("Package id 0"), "52000\n"),
+                (2, Some("Core 0"), "49000\n"),
+            ],
+            None,
+        );
+
+        let sensor = HwmonSensor::with_root(root.path());
+        let temp = sensor.get_cpu_temperature().unwrap();
+        assert!((temp - 52.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_amd_prefers_tdie_over_tctl() {
+        let root = TempDir::new().unwrap();
+        add_chip(
+            root.path(),
+            "hwmon1",
+            "k10temp",
+            "temp",
+            &[(1, Some("Tctl"), "61250\n"), (2, Some("Tdie"), "51250\n")],
+            None,
+        );
+
+        let sensor = HwmonSensor::with_root(root.path());
+        let package = sensor.find_cpu_package().unwrap();
+        assert_eq!(package.label.as_deref(), Some("Tdie"));
+        assert!((sensor.get_cpu_temperature().unwrap() - 51.25).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_unlabelled_cpu_chip_fallback() {
+        let root = TempDir::new().unwrap();
+        add_chip(
+            root.path(),
+            "hwmon2",
+            "zenpower",
+            "temp",
+            &[(1, None, "44000\n")],
+            None,
+        );
+
+        let sensor = HwmonSensor::with_root(root.path());
+        assert!((sensor.get_cpu_temperature().unwrap() - 44.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_cpu_thermal_chip() {
+        let root = TempDir::new().unwrap();
+        add_chip(
+            root.path(),
+            "hwmon0",
+            "cpu_thermal",
+            "temp",
+            &[(1, None, "52582\n")],
+            None,
+        );
+        add_chip(
+            root.path(),
+            "hwmon1",
+            "rp1_adc",
+            "temp",
+            &[(1, None, "60000\n")],
+            None,
+        );
+
+        let sensor = HwmonSensor::with_root(root.path());
+        assert_eq!(sensor.find_cpu_package().unwrap().chip, "cpu_thermal");
+        assert!((sensor.get_cpu_temperature().unwrap() - 52.582).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_no_cpu_chip() {
+        let root = TempDir::new().unwrap();
+        add_chip(
+            root.path(),
+            "hwmon0",
+            "nvme",
+            "temp",
+            &[(1, Some("Composite"), "38850\n")],
+            None,
+        );
+
+        let sensor = HwmonSensor::with_root(root.path());
+        assert!(!sensor.is_available());
+        assert!(sensor.get_cpu_temperature().is_err());
+    }
+
+    #[test]
+    fn test_temperatures_natural_order() {
+        let root = TempDir::new().unwrap();
+        add_chip(
+            root.path(),
+            "hwmon10",
+            "nvme",
+            "temp",
+            &[(1, None, "30000")],
+            None,
+        );
+        add_chip(
+            root.path(),
+            "hwmon2",
+            "acpitz",
+            "temp",
+            &[(10, None, "20000"), (2, None, "21000")],
+            None,
+        );
+
+        let sensor = HwmonSensor::with_root(root.path());
+        let temps = sensor.temperatures().unwrap();
+        let chips: Vec<&str> = temps.iter().map(|t| t.chip.as_str()).collect();
+        assert_eq!(chips, vec!["acpitz", "acpitz", "nvme"]);
+        assert!(temps[0].input.ends_with("temp2_input"));
+    }
+
+    #[test]
+    fn test_select_channel() {
+        let root = TempDir::new().unwrap();
+        add_chip(
+            root.path(),
+            "hwmon0",
+            "nvme",
+            "temp",
+            &[
+                (1, Some("Composite"), "38850\n"),
+                (2, Some("Sensor 1"), "40850\n"),
+            ],
+            None,
+        );
+
+        let sensor = HwmonSensor::with_root(root.path());
+        let composite = sensor.select("nvme").unwrap();
+        assert!((composite.read().unwrap() - 38.85).abs() < 0.01);
+        let sensor1 = sensor.select("nvme/Sensor 1").unwrap();
+        assert!((sensor1.read().unwrap() - 40.85).abs() < 0.01);
+        assert!(!sensor.select("nvme/Sensor 9").unwrap().is_available());
+    }
+
+    #[test]
+    fn test_discover_marks_default() {
+        let root = TempDir::new().unwrap();
+        add_chip(
+            root.path(),
+            "hwmon0",
+            "acpitz",
+            "temp",
+            &[(1, None, "27800\n")],
+            None,
+        );
+        add_chip(
+            root.path(),
+            "hwmon1",
+            "k10temp",
+            "temp",
+            &[(1, Some("Tctl"), "61250\n"), (3, Some("Tccd1"), "55000\n")],
+            None,
+        );
+
+        let readings = HwmonSensor::with_root(root.path()).discover();
+        assert_eq!(readings.len(), 3);
+        assert_eq!(readings[0].sensor, "hwmon:acpitz/temp1");
+        assert_eq!(readings[0].label, "temp1");
+        assert!(!readings[0].default);
+        assert_eq!(readings[1].sensor, "hwmon:k10temp/Tctl");
+        assert_eq!(readings[1].value, Some(61.25));
+        assert!(readings[1].default);
+        assert!(!readings[2].default);
+    }
+
+    #[test]
+    fn test_every_channel_selectable() {
+        let root = TempDir::new().unwrap();
+        add_chip(
+            root.path(),
+            "hwmon0",
+            "acpitz",
+            "temp",
+            &[(1, None, "27800\n"), (2, None, "29800\n")],
+            None,
+        );
+        add_chip(
+            root.path(),
+            "hwmon1",
+            "nvme",
+            "temp",
+            &[(1, Some("Composite"), "38850\n")],
+            None,
+        );
+        add_chip(
+            root.path(),
+            "hwmon2",
+            "nvme",
+            "temp",
+            &[(1, Some("Composite"), "44850\n")],
+            None,
+        );
+
+        let sensor = HwmonSensor::with_root(root.path());
+        let readings = sensor.discover();
+        let selectors: Vec<&str> = readings.iter().map(|r| r.sensor.as_str()).collect();
+        assert_eq!(
+            selectors,
+            vec![
+                "hwmon:acpitz/temp1",
+                "hwmon:acpitz/temp2",
+                "hwmon:hwmon1/Composite",
+                "hwmon:hwmon2/Composite",
+            ]
+        );
+
+        // Each printed selector reads back its own row
+        for reading in &readings {
+            let selector = reading.sensor.strip_prefix("hwmon:").unwrap();
+            let source = sensor.select(selector).unwrap();
+            assert_eq!(source.read().ok(), reading.value, "{}", selector);
+        }
+        // The chip name still picks the first chip of that name
+        assert!((sensor.select("nvme").unwrap().read().unwrap() - 38.85).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_fan_sensor_skips_stopped_fans() {
+        let root = TempDir::new().unwrap();
+        add_chip(
+            root.path(),
+            "hwmon0",
+            "k10temp",
+            "temp",
+            &[(1, Some("Tctl"), "61250\n")],
+            None,
+        );
+        add_chip(
+            root.path(),
+            "hwmon1",
+            "nct6798",
+            "fan",
+            &[(1, None, "0\n"), (2, None, "1187\n"), (3, None, "0\n")],
+            None,
+        );
+
+        let sensor = FanSensor::with_root(root.path());
+        assert_eq!(sensor.unit(), MetricUnit::Rpm);
+        assert_eq!(sensor.read().unwrap(), 1187.0);
+
+        let readings = sensor.discover();
+        assert_eq!(readings.len(), 3);
+        assert_eq!(readings[1].sensor, "fan:nct6798/fan2");
+        assert_eq!(readings[1].unit, MetricUnit::Rpm);
+        assert!(readings[1].default);
+        assert!(!readings[0].default);
+    }
+
+    #[test]
+    fn test_fan_sensor_select() {
+        let root = TempDir::new().unwrap();
+        add_chip(
+            root.path(),
+            "hwmon2",
+            "thinkpad",
+            "fan",
+            &[(1, Some("CPU Fan"), "2400\n"), (2, None, "1800\n")],
+            None,
+        );
+
+        let sensor = FanSensor::with_root(root.path());
+        assert_eq!(sensor.select("thinkpad").unwrap().read().unwrap(), 2400.0);
+        assert_eq!(
+            sensor.select("thinkpad/fan2").unwrap().read().unwrap(),
+            1800.0
+        );
+        assert!(!sensor.select("nct6798").unwrap().is_available());
+        assert!(!FanSensor::with_root(root.path().join("missing")).is_available());
+    }
+
+    #[test]
+    fn test_read_millidegrees_invalid() {
+        let root = TempDir::new().unwrap();
+        let path = root.path().join("temp1_input");
+        fs::write(&path, "garbage").unwrap();
+        assert!(read_millidegrees(&path).is_err());
+    }
+}
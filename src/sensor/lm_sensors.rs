@@ -0,0 +1,280 @@
+use crate::error::{OcypusError, Result};
+use crate::sensor::{self, MetricUnit, SensorReading, TemperatureSource};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::process::Command;
+use std::sync::Arc;
+
+/// One temperature input in `sensors -j` output
+#[derive(Debug, Clone, PartialEq)]
+pub struct SensorsTemp {
+    /// Chip name including the bus (e.g. `k10temp-pci-00c3`)
+    pub chip: String,
+    /// Feature label (e.g. `Tccd1`)
+    pub feature: String,
+    /// Input subfeature (e.g. `temp3_input`)
+    pub subfeature: String,
+    /// Temperature in °C
+    pub value: f32,
+}
+
+impl SensorsTemp {
+    /// Driver name of the chip (e.g. `k10temp`)
+    pub fn driver(&self) -> &str {
+        self.chip.split('-').next().unwrap_or(&self.chip)
+    }
+
+    /// Full `chip/feature/subfeature` path, as accepted by `select`
+    pub fn path(&self) -> String {
+        format!("{}/{}/{}", self.chip, self.feature, self.subfeature)
+    }
+
+    /// Discovery row for this input
+    pub fn to_reading(&self, default: bool) -> SensorReading {
+        SensorReading {
+            sensor: format!("sensors:{}", self.path()),
+            backend: "lm-sensors".to_string(),
+            chip: self.chip.clone(),
+            label: self.feature.clone(),
+            value: Some(self.value),
+            unit: MetricUnit::Celsius,
+            default,
+        }
+    }
+}
+
+/// Parse `sensors -j` output into its temperature inputs, ordered by chip
+/// name and then feature name
+pub fn parse_sensors_json(text: &str) -> Result<Vec<SensorsTemp>> {
+    let chips: BTreeMap<String, BTreeMap<String, Value>> = serde_json::from_str(text)
+        .map_err(|e| OcypusError::Sensor(format!("Invalid sensors -j output: {}", e)))?;
+
+    let mut temps = Vec::new();
+    for (chip, features) in chips {
+        // Features are objects; `Adapter` is a plain string
+        for (feature, subfeatures) in features {
+            let Value::Object(subfeatures) = subfeatures else {
+                continue;
+            };
+            for (subfeature, value) in subfeatures {
+                let is_input = subfeature.starts_with("temp") && subfeature.ends_with("_input");
+                if let (true, Some(value)) = (is_input, value.as_f64()) {
+                    temps.push(SensorsTemp {
+                        chip: chip.clone(),
+                        feature: feature.clone(),
+                        subfeature,
+                        value: value as f32,
+                    });
+                }
+            }
+        }
+    }
+    Ok(temps)
+}
+
+/// Find the CPU temperature input, ranked by `sensor::CPU_CHIPS`
+pub fn find_cpu(temps: &[SensorsTemp]) -> Option<&SensorsTemp> {
+    sensor::find_cpu(temps, SensorsTemp::driver, |t| Some(t.feature.as_str()))
+}
+
+/// Find the input selected by `chip[/feature[/subfeature]]`. The chip may be
+/// given by its full name or its driver name; a missing feature or
+/// subfeature takes the first one.
+pub fn find_path<'a>(temps: &'a [SensorsTemp], path: &str) -> Option<&'a SensorsTemp> {
+    let mut parts = path.splitn(3, '/');
+    let chip = parts.next().unwrap_or_default();
+    let feature = parts.next();
+    let subfeature = parts.next();
+
+    temps.iter().find(|t| {
+        (t.chip == chip || t.driver() == chip)
+            && feature.is_none_or(|f| t.feature == f)
+            && subfeature.is_none_or(|s| t.subfeature == s)
+    })
+}
+
+/// Run `sensors -j` and parse its temperatures
+pub fn read_sensors() -> Result<Vec<SensorsTemp>> {
+    let output = Command::new("sensors")
+        .arg("-j")
+        .output()
+        .map_err(|e| OcypusError::Sensor(format!("Failed to execute sensors command: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(OcypusError::Sensor(
+            "sensors command returned non-zero exit status".to_string(),
+        ));
+    }
+
+    parse_sensors_json(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Temperature from lm-sensors, selected by `chip/feature/subfeature`
+#[derive(Debug, Clone, Default)]
+pub struct LmSensorsSensor {
+    /// Selected input path; the CPU temperature if `None`
+    path: Option<String>,
+}
+
+impl LmSensorsSensor {
+    /// Create a sensor reading the CPU temperature
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a sensor reading the input at `chip[/feature[/subfeature]]`
+    pub fn with_path(path: impl Into<String>) -> Self {
+        Self {
+            path: Some(path.into()),
+        }
+    }
+
+    /// Pick this sensor's input from parsed output
+    pub fn find<'a>(&self, temps: &'a [SensorsTemp]) -> Result<&'a SensorsTemp> {
+        match &self.path {
+            Some(path) => find_path(temps, path).ok_or_else(|| {
+                OcypusError::Sensor(format!("'{}' not found in sensors output", path))
+            }),
+            None => find_cpu(temps).ok_or_else(|| {
+                OcypusError::Sensor("No CPU chip found in sensors output".to_string())
+            }),
+        }
+    }
+}
+
+impl TemperatureSource for LmSensorsSensor {
+    fn name(&self) -> &str {
+        "sensors"
+    }
+
+    fn is_available(&self) -> bool {
+        self.read().is_ok()
+    }
+
+    fn read(&self) -> Result<f32> {
+        let temps = read_sensors()?;
+        Ok(self.find(&temps)?.value)
+    }
+
+    fn discover(&self) -> Vec<SensorReading> {
+        let temps = read_sensors().unwrap_or_default();
+        let selected = self.find(&temps).ok();
+        temps
+            .iter()
+            .map(|t| t.to_reading(Some(t) == selected))
+            .collect()
+    }
+
+    /// Select an input as `chip[/feature[/subfeature]]`
+    fn select(&self, selector: &str) -> Result<Arc<dyn TemperatureSource>> {
+        Ok(Arc::new(Self::with_path(selector)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AMD: &str = r#"{
+   "acpitz-acpi-0":{
+      "Adapter": "ACPI interface",
+      "temp1":{
+         "temp1_input": 16.800,
+         "temp1_crit": 20.800
+      }
+   },
+   "nvme-pci-0100":{
+      "Adapter": "PCI adapter",
+      "Composite":{
+         "temp1_input": 35.850,
+         "temp1_max": 81.850
+      }
+   },
+   "k10temp-pci-00c3":{
+      "Adapter": "PCI adapter",
+      "Tctl":{
+         "temp1_input": 45.250
+      },
+      "Tccd1":{
+         "temp3_input": 40.750
+      }
+   },
+   "amdgpu-pci-0300":{
+      "Adapter": "PCI adapter",
+      "vddgfx":{
+         "in0_input": 0.750
+      },
+      "edge":{
+         "temp1_input": 38.000,
+         "temp1_crit": 100.000
+      }
+   }
+}"#;
+
+    #[test]
+    fn test_parse_sensors_json() {
+        let temps = parse_sensors_json(AMD).unwrap();
+        let paths: Vec<String> = temps.iter().map(SensorsTemp::path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "acpitz-acpi-0/temp1/temp1_input",
+                "amdgpu-pci-0300/edge/temp1_input",
+                "k10temp-pci-00c3/Tccd1/temp3_input",
+                "k10temp-pci-00c3/Tctl/temp1_input",
+                "nvme-pci-0100/Composite/temp1_input",
+            ]
+        );
+        assert_eq!(temps[0].value, 16.8);
+        assert_eq!(temps[2].driver(), "k10temp");
+
+        assert!(parse_sensors_json("k10temp-pci-00c3\nTctl: +45.2°C").is_err());
+    }
+
+    #[test]
+    fn test_find_cpu_ranks_known_chips() {
+        // The ACPI and NVMe chips sort first but are never the CPU
+        let temps = parse_sensors_json(AMD).unwrap();
+        assert_eq!(
+            find_cpu(&temps).unwrap().path(),
+            "k10temp-pci-00c3/Tctl/temp1_input"
+        );
+
+        let intel = r#"{
+           "coretemp-isa-0000":{
+              "Adapter": "ISA adapter",
+              "Core 0":{ "temp2_input": 51.000 },
+              "Package id 0":{ "temp1_input": 55.000 }
+           },
+           "acpitz-acpi-0":{ "temp1":{ "temp1_input": 27.800 } }
+        }"#;
+        let temps = parse_sensors_json(intel).unwrap();
+        assert_eq!(find_cpu(&temps).unwrap().value, 55.0);
+
+        // Without a preferred label, the chip's first temperature
+        let arm = r#"{ "cpu_thermal-virtual-0":{ "temp1":{ "temp1_input": 48.3 } } }"#;
+        let temps = parse_sensors_json(arm).unwrap();
+        assert_eq!(find_cpu(&temps).unwrap().value, 48.3);
+
+        let temps =
+            parse_sensors_json(r#"{ "nvme-pci-0100":{ "Composite":{ "temp1_input": 35.0 } } }"#)
+                .unwrap();
+        assert_eq!(find_cpu(&temps), None);
+    }
+
+    #[test]
+    fn test_find_path() {
+        let temps = parse_sensors_json(AMD).unwrap();
+        let value = |path: &str| find_path(&temps, path).map(|t| t.value);
+
+        assert_eq!(value("k10temp-pci-00c3/Tccd1/temp3_input"), Some(40.75));
+        assert_eq!(value("k10temp/Tccd1"), Some(40.75));
+        assert_eq!(value("nvme-pci-0100"), Some(35.85));
+        assert_eq!(value("k10temp-pci-00c3/Tccd1/temp1_input"), None);
+        assert_eq!(value("k10temp/Tccd2"), None);
+
+        let sensor = LmSensorsSensor::with_path("amdgpu/edge");
+        assert_eq!(sensor.find(&temps).unwrap().value, 38.0);
+        assert!(LmSensorsSensor::with_path("it87").find(&temps).is_err());
+    }
+}
@@ -1,3 +1,205 @@
 /// Sensor modules for temperature monitoring
+pub mod aggregate;
+pub mod cpu_load;
 pub mod cpu_sensor;
-pub mod gpu_sensor;
\ No newline at end of file
+pub mod drm;
+pub mod gpu_metrics;
+pub mod gpu_sensor;
+pub mod hwmon;
+pub mod lm_sensors;
+pub mod rocm;
+pub mod thermal_zone;
+
+use crate::config::TemperatureUnit;
+use crate::error::{OcypusError, Result};
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+
+/// Unit of the values a source produces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricUnit {
+    /// Temperature in °C, shown in the configured temperature unit
+    #[default]
+    Celsius,
+    /// Utilisation, 0-100
+    Percent,
+    /// Fan speed in revolutions per minute
+    Rpm,
+    /// Power draw in watts
+    Watts,
+}
+
+impl MetricUnit {
+    /// Whether values are temperatures subject to °C/°F conversion
+    pub fn is_temperature(self) -> bool {
+        self == MetricUnit::Celsius
+    }
+
+    /// Suffix printed after a value, with temperatures in `unit`
+    pub fn suffix(self, unit: TemperatureUnit) -> String {
+        match self {
+            MetricUnit::Celsius => format!("°{}", unit.as_char()),
+            MetricUnit::Percent => "%".to_string(),
+            MetricUnit::Rpm => " RPM".to_string(),
+            MetricUnit::Watts => " W".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for MetricUnit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            MetricUnit::Celsius => "temperature",
+            MetricUnit::Percent => "load",
+            MetricUnit::Rpm => "fan speed",
+            MetricUnit::Watts => "power",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A value read from a source, tagged with its unit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    pub value: f32,
+    pub unit: MetricUnit,
+}
+
+impl Measurement {
+    pub fn new(value: f32, unit: MetricUnit) -> Self {
+        Self { value, unit }
+    }
+
+    /// A temperature in °C
+    pub fn celsius(value: f32) -> Self {
+        Self::new(value, MetricUnit::Celsius)
+    }
+
+    /// The value as shown on the display: temperatures are converted to
+    /// `unit`, everything else is passed through
+    pub fn display_value(&self, unit: TemperatureUnit) -> f32 {
+        if self.unit.is_temperature() {
+            unit.from_celsius(self.value)
+        } else {
+            self.value
+        }
+    }
+}
+
+/// One reading found while discovering sensors
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SensorReading {
+    /// `--sensor` value that selects this reading
+    pub sensor: String,
+    /// Backend that produced it (e.g. `hwmon`, `nvidia-smi`)
+    pub backend: String,
+    /// Chip, device or tool the reading comes from
+    pub chip: String,
+    /// Channel label within the chip
+    pub label: String,
+    /// Current value in `unit`, or `None` if it could not be read
+    pub value: Option<f32>,
+    /// Unit of `value`
+    pub unit: MetricUnit,
+    /// Whether the source uses this reading when no selector is given
+    pub default: bool,
+}
+
+/// A pluggable metric backend. Most sources report temperatures; others
+/// override `unit` to report load, fan speed or power instead.
+pub trait TemperatureSource: Send + Sync {
+    /// Name used to select this source (e.g. `cpu`)
+    fn name(&self) -> &str;
+
+    /// Check if the source can currently produce readings
+    fn is_available(&self) -> bool;
+
+    /// Read the current value, in °C for temperature sources
+    fn read(&self) -> Result<f32>;
+
+    /// Unit of the values returned by `read`
+    fn unit(&self) -> MetricUnit {
+        MetricUnit::Celsius
+    }
+
+    /// Read the current value along with its unit
+    fn measure(&self) -> Result<Measurement> {
+        Ok(Measurement::new(self.read()?, self.unit()))
+    }
+
+    /// List every reading this source can see. The default lists a single
+    /// reading from `read`.
+    fn discover(&self) -> Vec<SensorReading> {
+        vec![SensorReading {
+            sensor: self.name().to_string(),
+            backend: self.name().to_string(),
+            chip: "-".to_string(),
+            label: "-".to_string(),
+            value: self.read().ok(),
+            unit: self.unit(),
+            default: true,
+        }]
+    }
+
+    /// Create a copy of this source narrowed by a selector
+    /// (the part after `:` in `--sensor name:selector`)
+    fn select(&self, selector: &str) -> Result<Arc<dyn TemperatureSource>> {
+        Err(OcypusError::Config(format!(
+            "Sensor '{}' does not accept a selector (got '{}')",
+            self.name(),
+            selector
+        )))
+    }
+}
+
+/// Chips that report the CPU temperature, best first, each with its channel
+/// labels in order of preference. Chips are matched by driver name: the
+/// hwmon `name`, or the lm-sensors chip name before the bus (`k10temp` in
+/// `k10temp-pci-00c3`). Shared by the hwmon and lm-sensors backends so both
+/// pick the same channel.
+pub const CPU_CHIPS: &[(&str, &[&str])] = &[
+    ("coretemp", &["Package id 0"]),
+    ("k10temp", &["Tdie", "Tctl"]),
+    ("zenpower", &["Tdie", "Tctl"]),
+    // Raspberry Pi and other ARM boards
+    ("cpu_thermal", &[]),
+];
+
+/// Find the CPU temperature among `temps`: the first chip in `CPU_CHIPS`
+/// that is present, using its preferred label or else its first channel.
+/// Other chips (ACPI, NVMe, ...) are never picked.
+pub fn find_cpu<T>(
+    temps: &[T],
+    driver: impl Fn(&T) -> &str,
+    label: impl Fn(&T) -> Option<&str>,
+) -> Option<&T> {
+    CPU_CHIPS.iter().find_map(|(chip, labels)| {
+        let chip_temps: Vec<&T> = temps.iter().filter(|t| driver(t) == *chip).collect();
+        labels
+            .iter()
+            .find_map(|wanted| chip_temps.iter().find(|t| label(t) == Some(*wanted)))
+            .or_else(|| chip_temps.first())
+            .copied()
+    })
+}
+
+/// Built-in sources, in the order they are listed
+pub fn default_sources() -> Vec<Arc<dyn TemperatureSource>> {
+    vec![
+        Arc::new(cpu_sensor::CpuSensor),
+        Arc::new(gpu_sensor::GpuSensor::new()),
+        Arc::new(hwmon::HwmonSensor::new()),
+        Arc::new(thermal_zone::ThermalZoneSensor::new(None)),
+        Arc::new(lm_sensors::LmSensorsSensor::new()),
+        Arc::new(cpu_load::CpuLoadSensor::new()),
+        Arc::new(gpu_metrics::GpuMetricSensor::new(
+            gpu_metrics::GpuMetric::Utilization,
+        )),
+        Arc::new(gpu_metrics::GpuMetricSensor::new(
+            gpu_metrics::GpuMetric::Power,
+        )),
+        Arc::new(hwmon::FanSensor::new()),
+    ]
+}
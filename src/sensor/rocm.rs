@@ -0,0 +1,414 @@
+//! JSON output of the AMD ROCm tools: `amd-smi` and its predecessor
+//! `rocm-smi`.
+//!
+//! The layout changed between ROCm releases. amd-smi printed bare numbers in
+//! ROCm 6.0, `{"value": .., "unit": ..}` objects from 6.1, and wraps the GPU
+//! list in `gpu_data` since 6.4. rocm-smi prints every value as a string,
+//! keyed by its human-readable label. The types below accept each variant.
+//! These layouts have not been checked against captured output yet; see
+//! `tests/fixtures/README.md`.
+
+use crate::error::{OcypusError, Result};
+use crate::sensor::gpu_metrics::GpuMetric;
+use crate::sensor::gpu_sensor::GpuTemp;
+use log::debug;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// A value as amd-smi prints it
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum AmdSmiValue {
+    /// ROCm 6.0: `41`
+    Number(f32),
+    /// ROCm 6.1 and later: `{"value": 41, "unit": "C"}`
+    WithUnit { value: Box<AmdSmiValue> },
+    /// `N/A` for unsupported fields
+    Text(String),
+}
+
+impl AmdSmiValue {
+    fn number(&self) -> Option<f32> {
+        match self {
+            AmdSmiValue::Number(number) => Some(*number),
+            AmdSmiValue::WithUnit { value } => value.number(),
+            AmdSmiValue::Text(text) => text.trim().parse().ok(),
+        }
+    }
+}
+
+/// The first field that holds a number
+fn first_number(values: &[&Option<AmdSmiValue>]) -> Option<f32> {
+    values
+        .iter()
+        .find_map(|value| value.as_ref().and_then(AmdSmiValue::number))
+}
+
+/// `amd-smi ... --json` output
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum AmdSmiOutput {
+    Gpus(Vec<AmdSmiGpu>),
+    /// ROCm 6.4 and later
+    Wrapped {
+        gpu_data: Vec<AmdSmiGpu>,
+    },
+}
+
+impl AmdSmiOutput {
+    fn parse(text: &str) -> Result<Vec<AmdSmiGpu>> {
+        let output = serde_json::from_str(text)
+            .map_err(|e| OcypusError::Sensor(format!("Invalid amd-smi JSON output: {}", e)))?;
+        Ok(match output {
+            AmdSmiOutput::Gpus(gpus) | AmdSmiOutput::Wrapped { gpu_data: gpus } => gpus,
+        })
+    }
+}
+
+/// One GPU of `amd-smi metric --json` or `amd-smi list --json`; each
+/// command fills in its own sections
+#[derive(Debug, Deserialize)]
+struct AmdSmiGpu {
+    gpu: u32,
+    #[serde(default)]
+    bdf: Option<String>,
+    #[serde(default)]
+    uuid: Option<String>,
+    #[serde(default)]
+    usage: Option<AmdSmiUsage>,
+    #[serde(default)]
+    power: Option<AmdSmiPower>,
+    #[serde(default)]
+    temperature: Option<AmdSmiTemperature>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AmdSmiUsage {
+    gfx_activity: Option<AmdSmiValue>,
+}
+
+/// Socket power went by several names across releases
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AmdSmiPower {
+    socket_power: Option<AmdSmiValue>,
+    average_socket_power: Option<AmdSmiValue>,
+    current_socket_power: Option<AmdSmiValue>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct AmdSmiTemperature {
+    edge: Option<AmdSmiValue>,
+    hotspot: Option<AmdSmiValue>,
+    mem: Option<AmdSmiValue>,
+}
+
+/// Parse `amd-smi metric --temperature --json` output, taking bus ids and
+/// UUIDs from `amd-smi list --json` output if given. The temperatures are
+/// still used when the list can't be parsed, just without ids. Cards without
+/// any temperature are left out.
+pub fn parse_amd_smi_gpus(metric: &str, list: Option<&str>) -> Result<Vec<GpuTemp>> {
+    let ids = match list.map(AmdSmiOutput::parse) {
+        Some(Ok(ids)) => ids,
+        Some(Err(e)) => {
+            debug!("Ignoring amd-smi list output: {}", e);
+            Vec::new()
+        }
+        None => Vec::new(),
+    };
+
+    Ok(AmdSmiOutput::parse(metric)?
+        .into_iter()
+        .filter_map(|gpu| {
+            let temperature = gpu.temperature?;
+            let info = ids.iter().find(|id| id.gpu == gpu.gpu);
+            let card = GpuTemp {
+                index: gpu.gpu,
+                uuid: info.and_then(|id| id.uuid.clone()),
+                pci: info
+                    .and_then(|id| id.bdf.as_deref())
+                    .and_then(|bdf| bdf.parse().ok()),
+                edge: first_number(&[&temperature.edge]),
+                junction: first_number(&[&temperature.hotspot]),
+                memory: first_number(&[&temperature.mem]),
+            };
+            card.has_temperature().then_some(card)
+        })
+        .collect())
+}
+
+/// Parse `amd-smi metric --usage --json` or `--power --json` output for the
+/// first GPU that reports the metric
+pub fn parse_amd_smi_metric(text: &str, metric: GpuMetric) -> Result<f32> {
+    AmdSmiOutput::parse(text)?
+        .iter()
+        .find_map(|gpu| match metric {
+            GpuMetric::Utilization => first_number(&[&gpu.usage.as_ref()?.gfx_activity]),
+            GpuMetric::Power => {
+                let power = gpu.power.as_ref()?;
+                first_number(&[
+                    &power.socket_power,
+                    &power.current_socket_power,
+                    &power.average_socket_power,
+                ])
+            }
+        })
+        .ok_or_else(|| {
+            OcypusError::Sensor(format!("No GPU {} found in amd-smi output", metric.unit()))
+        })
+}
+
+/// One `cardN` object of `rocm-smi --json` output
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct RocmSmiCard {
+    #[serde(rename = "Temperature (Sensor edge) (C)")]
+    edge: Option<String>,
+    #[serde(rename = "Temperature (Sensor junction) (C)")]
+    junction: Option<String>,
+    #[serde(rename = "Temperature (Sensor memory) (C)")]
+    memory: Option<String>,
+    #[serde(rename = "PCI Bus")]
+    pci_bus: Option<String>,
+    #[serde(rename = "Unique ID")]
+    unique_id: Option<String>,
+    #[serde(rename = "GPU use (%)")]
+    gpu_use: Option<String>,
+    /// ROCm 5 and earlier
+    #[serde(rename = "Average Graphics Package Power (W)")]
+    average_power: Option<String>,
+    /// ROCm 6
+    #[serde(rename = "Current Socket Graphics Package Power (W)")]
+    current_power: Option<String>,
+}
+
+/// Parse a string field, treating `N/A` and the like as missing
+fn rocm_number(value: &Option<String>) -> Option<f32> {
+    value.as_deref()?.trim().parse().ok()
+}
+
+/// The `cardN` objects of `rocm-smi --json` output, by index. Other keys
+/// (such as `system`) are skipped.
+fn parse_rocm_smi(text: &str) -> Result<Vec<(u32, RocmSmiCard)>> {
+    let invalid =
+        |e: serde_json::Error| OcypusError::Sensor(format!("Invalid rocm-smi JSON output: {}", e));
+    let output: BTreeMap<String, Value> = serde_json::from_str(text).map_err(invalid)?;
+
+    let mut cards = Vec::new();
+    for (key, value) in output {
+        let Some(Ok(index)) = key.strip_prefix("card").map(str::parse::<u32>) else {
+            continue;
+        };
+        cards.push((index, RocmSmiCard::deserialize(value).map_err(invalid)?));
+    }
+    // `card10` sorts before `card2` as a key
+    cards.sort_by_key(|(index, _)| *index);
+    Ok(cards)
+}
+
+/// Parse `rocm-smi --showtemp --showbus --showuniqueid --json` output.
+/// Cards without any temperature are left out.
+pub fn parse_rocm_smi_gpus(text: &str) -> Result<Vec<GpuTemp>> {
+    Ok(parse_rocm_smi(text)?
+        .into_iter()
+        .filter_map(|(index, card)| {
+            let gpu = GpuTemp {
+                index,
+                uuid: card.unique_id.filter(|id| id != "N/A"),
+                pci: card.pci_bus.as_deref().and_then(|bus| bus.parse().ok()),
+                edge: rocm_number(&card.edge),
+                junction: rocm_number(&card.junction),
+                memory: rocm_number(&card.memory),
+            };
+            gpu.has_temperature().then_some(gpu)
+        })
+        .collect())
+}
+
+/// Parse `rocm-smi --showuse --json` or `--showpower --json` output for the
+/// first card that reports the metric
+pub fn parse_rocm_smi_metric(text: &str, metric: GpuMetric) -> Result<f32> {
+    parse_rocm_smi(text)?
+        .iter()
+        .find_map(|(_, card)| match metric {
+            GpuMetric::Utilization => rocm_number(&card.gpu_use),
+            GpuMetric::Power => {
+                rocm_number(&card.current_power).or_else(|| rocm_number(&card.average_power))
+            }
+        })
+        .ok_or_else(|| {
+            OcypusError::Sensor(format!("No GPU {} found in rocm-smi output", metric.unit()))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sensor::gpu_sensor::{GpuTempKind, PciAddress};
+
+    /// Outputs written by hand in the layout of each ROCm release, not
+    /// captured from a card; see `tests/fixtures/README.md`
+    fn fixture(name: &str) -> String {
+        let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+        std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+    }
+
+    fn pci(bus: &str) -> Option<PciAddress> {
+        Some(bus.parse().unwrap())
+    }
+
+    #[test]
+    fn test_amd_smi_rocm_6_0() {
+        let metric = fixture("amd-smi/synthetic-rocm-6.0-metric.json");
+        let list = fixture("amd-smi/synthetic-rocm-6.0-list.json");
+        let gpus = parse_amd_smi_gpus(&metric, Some(&list)).unwrap();
+        assert_eq!(
+            gpus,
+            vec![GpuTemp {
+                index: 0,
+                uuid: Some("1fff73bf-0000-1000-80ea-8e1a3a0be7a4".to_string()),
+                pci: pci("0000:03:00.0"),
+                edge: Some(41.0),
+                junction: Some(44.0),
+                memory: Some(52.0),
+            }]
+        );
+
+        assert_eq!(
+            parse_amd_smi_metric(&metric, GpuMetric::Utilization).unwrap(),
+            12.0
+        );
+        assert_eq!(
+            parse_amd_smi_metric(&metric, GpuMetric::Power).unwrap(),
+            35.0
+        );
+    }
+
+    #[test]
+    fn test_amd_smi_rocm_6_2() {
+        let metric = fixture("amd-smi/synthetic-rocm-6.2-metric.json");
+        let gpus = parse_amd_smi_gpus(&metric, None).unwrap();
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(
+            (gpus[0].edge, gpus[0].junction, gpus[0].memory),
+            (Some(38.0), Some(40.0), None)
+        );
+        assert_eq!(gpus[0].pci, None);
+        assert_eq!(gpus[1].index, 1);
+        assert_eq!(gpus[1].junction, Some(71.0));
+
+        // The GPU index comes first in the output but is not a reading
+        assert_eq!(
+            parse_amd_smi_metric(&metric, GpuMetric::Utilization).unwrap(),
+            3.0
+        );
+        assert_eq!(
+            parse_amd_smi_metric(&metric, GpuMetric::Power).unwrap(),
+            17.0
+        );
+    }
+
+    #[test]
+    fn test_amd_smi_rocm_6_4() {
+        let metric = fixture("amd-smi/synthetic-rocm-6.4-metric.json");
+        let list = fixture("amd-smi/synthetic-rocm-6.4-list.json");
+        let gpus = parse_amd_smi_gpus(&metric, Some(&list)).unwrap();
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].pci, pci("0000:c3:00.0"));
+        assert_eq!(
+            (gpus[0].edge, gpus[0].junction, gpus[0].memory),
+            (Some(47.0), Some(53.0), Some(60.0))
+        );
+        assert_eq!(
+            parse_amd_smi_metric(&metric, GpuMetric::Power).unwrap(),
+            64.0
+        );
+    }
+
+    #[test]
+    fn test_amd_smi_invalid() {
+        assert!(parse_amd_smi_gpus("GPU: 0\n    EDGE: 41 °C", None).is_err());
+        assert!(parse_amd_smi_gpus("[]", None).unwrap().is_empty());
+        assert!(parse_amd_smi_metric("[]", GpuMetric::Power).is_err());
+
+        // A list that can't be parsed only loses the ids
+        let metric = fixture("amd-smi/synthetic-rocm-6.0-metric.json");
+        let gpus = parse_amd_smi_gpus(&metric, Some("amd-smi: command failed")).unwrap();
+        assert_eq!(gpus.len(), 1);
+        assert_eq!((gpus[0].pci.as_ref(), gpus[0].edge), (None, Some(41.0)));
+    }
+
+    #[test]
+    fn test_rocm_smi_rocm_5_7() {
+        let text = fixture("rocm-smi/synthetic-rocm-5.7.json");
+        let gpus = parse_rocm_smi_gpus(&text).unwrap();
+        assert_eq!(
+            gpus,
+            vec![GpuTemp {
+                index: 0,
+                uuid: Some("0x9246a8e1e2c0bd02".to_string()),
+                pci: pci("0000:03:00.0"),
+                edge: Some(42.0),
+                junction: Some(47.0),
+                memory: Some(56.0),
+            }]
+        );
+        assert_eq!(
+            parse_rocm_smi_metric(&text, GpuMetric::Utilization).unwrap(),
+            5.0
+        );
+        assert_eq!(
+            parse_rocm_smi_metric(&text, GpuMetric::Power).unwrap(),
+            27.0
+        );
+    }
+
+    #[test]
+    fn test_rocm_smi_junction_first() {
+        // Each temperature is classified by its key, not by its position
+        let text = r#"{
+            "card0": {
+                "Temperature (Sensor junction) (C)": "47.0",
+                "Temperature (Sensor edge) (C)": "42.0",
+                "PCI Bus": "0000:03:00.0"
+            },
+            "card1": {
+                "Temperature (Sensor junction) (C)": "63.0",
+                "Temperature (Sensor memory) (C)": "N/A"
+            }
+        }"#;
+        let gpus = parse_rocm_smi_gpus(text).unwrap();
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(
+            (gpus[0].edge, gpus[0].junction, gpus[0].memory),
+            (Some(42.0), Some(47.0), None)
+        );
+        assert_eq!(gpus[0].temperature(GpuTempKind::Edge), Some(42.0));
+        assert_eq!(
+            (gpus[1].edge, gpus[1].junction, gpus[1].memory),
+            (None, Some(63.0), None)
+        );
+    }
+
+    #[test]
+    fn test_rocm_smi_rocm_6_2() {
+        let text = fixture("rocm-smi/synthetic-rocm-6.2.json");
+        let gpus = parse_rocm_smi_gpus(&text).unwrap();
+        // card2 is an integrated GPU without any temperature sensor
+        assert_eq!(gpus.len(), 2);
+        assert_eq!(gpus[0].index, 0);
+        assert_eq!(
+            (gpus[1].edge, gpus[1].junction, gpus[1].memory),
+            (Some(57.0), Some(63.0), None)
+        );
+        assert_eq!(gpus[1].uuid, None);
+        assert_eq!(gpus[1].pci, pci("0000:0c:00.0"));
+        assert_eq!(
+            parse_rocm_smi_metric(&text, GpuMetric::Power).unwrap(),
+            31.0
+        );
+    }
+}
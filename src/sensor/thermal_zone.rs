@@ -0,0 +1,277 @@
+use crate::error::{OcypusError, Result};
+use crate::sensor::hwmon::{read_millidegrees, sorted_entries};
+use crate::sensor::{MetricUnit, SensorReading, TemperatureSource};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Default sysfs thermal class directory
+pub const DEFAULT_THERMAL_ROOT: &str = "/sys/class/thermal";
+
+/// Zone types that describe the CPU, in order of preference
+const CPU_ZONE_TYPES: &[&str] = &["x86_pkg_temp", "cpu-thermal", "cpu_thermal", "soc_thermal"];
+
+/// A thermal zone exposed under `/sys/class/thermal`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThermalZone {
+    /// Zone index (`thermal_zone<N>`)
+    pub index: u32,
+    /// Zone type from the `type` attribute (e.g. `x86_pkg_temp`)
+    pub zone_type: String,
+    /// Path to the zone directory
+    pub path: PathBuf,
+}
+
+impl ThermalZone {
+    /// Read the zone temperature in °C
+    pub fn read_temperature(&self) -> Result<f32> {
+        read_millidegrees(&self.path.join("temp"))
+    }
+
+    /// Directory name, `thermal_zone<N>`
+    pub fn name(&self) -> String {
+        format!("thermal_zone{}", self.index)
+    }
+
+    /// Whether `wanted` names this zone, by type or by directory name
+    fn matches(&self, wanted: &str) -> bool {
+        self.zone_type == wanted || self.name() == wanted
+    }
+
+    /// Selector naming this zone: its type, or its directory name when
+    /// another zone has the same type (several `acpitz` zones are common)
+    pub fn selector(&self, shared_type: bool) -> String {
+        if shared_type {
+            self.name()
+        } else {
+            self.zone_type.clone()
+        }
+    }
+}
+
+/// Thermal zone temperature sensor
+#[derive(Debug, Clone)]
+pub struct ThermalZoneSensor {
+    root: PathBuf,
+    zone_type: Option<String>,
+}
+
+impl ThermalZoneSensor {
+    /// Create a sensor for the given zone type or `thermal_zone<N>` name, or
+    /// the best CPU zone if `None`
+    pub fn new(zone_type: Option<String>) -> Self {
+        Self::with_root(DEFAULT_THERMAL_ROOT, zone_type)
+    }
+
+    /// Create a sensor reading from a custom thermal root
+    pub fn with_root(root: impl Into<PathBuf>, zone_type: Option<String>) -> Self {
+        Self {
+            root: root.into(),
+            zone_type,
+        }
+    }
+
+    /// List all thermal zones
+    pub fn zones(&self) -> Result<Vec<ThermalZone>> {
+        let mut zones = Vec::new();
+
+        for path in sorted_entries(&self.root, "thermal_zone")? {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let Some(index) = name
+                .strip_prefix("thermal_zone")
+                .and_then(|n| n.parse::<u32>().ok())
+            else {
+                continue;
+            };
+
+            let zone_type = match fs::read_to_string(path.join("type")) {
+                Ok(t) => t.trim().to_string(),
+                Err(_) => continue,
+            };
+
+            zones.push(ThermalZone {
+                index,
+                zone_type,
+                path,
+            });
+        }
+
+        Ok(zones)
+    }
+
+    /// Find the configured zone
+    pub fn find_zone(&self) -> Result<ThermalZone> {
+        let zones = self.zones()?;
+
+        let found = match &self.zone_type {
+            Some(wanted) => zones.iter().find(|z| z.matches(wanted)),
+            None => CPU_ZONE_TYPES
+                .iter()
+                .find_map(|wanted| zones.iter().find(|z| z.zone_type == *wanted))
+                .or_else(|| zones.first()),
+        };
+
+        found.cloned().ok_or_else(|| {
+            let available: Vec<&str> = zones.iter().map(|z| z.zone_type.as_str()).collect();
+            OcypusError::Sensor(format!(
+                "Thermal zone '{}' not found (available: {})",
+                self.zone_type.as_deref().unwrap_or("cpu"),
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                }
+            ))
+        })
+    }
+}
+
+impl TemperatureSource for ThermalZoneSensor {
+    fn name(&self) -> &str {
+        "thermal"
+    }
+
+    /// Check if the configured zone exists
+    fn is_available(&self) -> bool {
+        self.find_zone().is_ok()
+    }
+
+    /// Get the temperature of the configured zone in °C
+    fn read(&self) -> Result<f32> {
+        self.find_zone()?.read_temperature()
+    }
+
+    fn discover(&self) -> Vec<SensorReading> {
+        let selected = self.find_zone().ok();
+        let zones = self.zones().unwrap_or_default();
+        let shared_type = |zone: &ThermalZone| {
+            zones
+                .iter()
+                .filter(|z| z.zone_type == zone.zone_type)
+                .count()
+                > 1
+        };
+        zones
+            .iter()
+            .map(|zone| SensorReading {
+                sensor: format!("thermal:{}", zone.selector(shared_type(zone))),
+                backend: "thermal".to_string(),
+                chip: zone.name(),
+                label: zone.zone_type.clone(),
+                value: zone.read_temperature().ok(),
+                unit: MetricUnit::Celsius,
+                default: Some(zone) == selected.as_ref(),
+            })
+            .collect()
+    }
+
+    fn select(&self, selector: &str) -> Result<Arc<dyn TemperatureSource>> {
+        Ok(Arc::new(Self::with_root(
+            self.root.clone(),
+            Some(selector.to_string()),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn add_zone(root: &Path, index: u32, zone_type: &str, temp: &str) {
+        let zone = root.join(format!("thermal_zone{}", index));
+        fs::create_dir_all(&zone).unwrap();
+        fs::write(zone.join("type"), format!("{}\n", zone_type)).unwrap();
+        fs::write(zone.join("temp"), temp).unwrap();
+    }
+
+    #[test]
+    fn test_list_zones() {
+        let root = TempDir::new().unwrap();
+        add_zone(root.path(), 1, "x86_pkg_temp", "48000\n");
+        add_zone(root.path(), 0, "acpitz", "27800\n");
+        fs::create_dir_all(root.path().join("cooling_device0")).unwrap();
+
+        let sensor = ThermalZoneSensor::with_root(root.path(), None);
+        let zones = sensor.zones().unwrap();
+        assert_eq!(zones.len(), 2);
+        assert_eq!(zones[0].zone_type, "acpitz");
+        assert_eq!(zones[1].index, 1);
+    }
+
+    #[test]
+    fn test_select_zone_by_type() {
+        let root = TempDir::new().unwrap();
+        add_zone(root.path(), 0, "cpu-thermal", "41500\n");
+        add_zone(root.path(), 1, "gpu-thermal", "39000\n");
+
+        let sensor = ThermalZoneSensor::with_root(root.path(), Some("gpu-thermal".to_string()));
+        assert!((sensor.read().unwrap() - 39.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_default_prefers_cpu_zone() {
+        let root = TempDir::new().unwrap();
+        add_zone(root.path(), 0, "acpitz", "27800\n");
+        add_zone(root.path(), 2, "x86_pkg_temp", "55000\n");
+
+        let sensor = ThermalZoneSensor::with_root(root.path(), None);
+        assert_eq!(sensor.find_zone().unwrap().zone_type, "x86_pkg_temp");
+        assert!((sensor.read().unwrap() - 55.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_discover_zones() {
+        let root = TempDir::new().unwrap();
+        add_zone(root.path(), 0, "acpitz", "27800\n");
+        add_zone(root.path(), 1, "cpu-thermal", "41500\n");
+
+        let readings = ThermalZoneSensor::with_root(root.path(), None).discover();
+        assert_eq!(readings.len(), 2);
+        assert_eq!(readings[1].sensor, "thermal:cpu-thermal");
+        assert_eq!(readings[1].chip, "thermal_zone1");
+        assert!(readings[1].default);
+        assert!(!readings[0].default);
+    }
+
+    #[test]
+    fn test_zones_sharing_a_type() {
+        let root = TempDir::new().unwrap();
+        add_zone(root.path(), 0, "acpitz", "27800\n");
+        add_zone(root.path(), 1, "acpitz", "29800\n");
+        add_zone(root.path(), 2, "x86_pkg_temp", "48000\n");
+
+        let sensor = ThermalZoneSensor::with_root(root.path(), None);
+        let readings = sensor.discover();
+        let selectors: Vec<&str> = readings.iter().map(|r| r.sensor.as_str()).collect();
+        assert_eq!(
+            selectors,
+            [
+                "thermal:thermal_zone0",
+                "thermal:thermal_zone1",
+                "thermal:x86_pkg_temp"
+            ]
+        );
+
+        // Every listed selector reads its own zone
+        for reading in &readings {
+            let (_, selector) = reading.sensor.split_once(':').unwrap();
+            let selected = sensor.select(selector).unwrap();
+            assert_eq!(selected.read().ok(), reading.value, "{}", selector);
+        }
+        let second = sensor.select("thermal_zone1").unwrap();
+        assert!((second.read().unwrap() - 29.8).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_missing_zone_lists_available() {
+        let root = TempDir::new().unwrap();
+        add_zone(root.path(), 0, "acpitz", "27800\n");
+
+        let sensor = ThermalZoneSensor::with_root(root.path(), Some("cpu-thermal".to_string()));
+        assert!(!sensor.is_available());
+        let err = sensor.read().unwrap_err().to_string();
+        assert!(err.contains("acpitz"), "unexpected error: {}", err);
+    }
+}
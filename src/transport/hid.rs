@@ -0,0 +1,73 @@
+use crate::device::DeviceInfo;
+use crate::error::{OcypusError, Result};
+use crate::transport::{HidConnection, HidTransport};
+use hidapi::{HidApi, HidDevice};
+use std::ffi::CString;
+
+/// Transport backed by the system hidapi library
+pub struct HidApiTransport {
+    api: HidApi,
+}
+
+impl HidApiTransport {
+    /// Initialize hidapi
+    pub fn new() -> Result<Self> {
+        let api = HidApi::new().map_err(|e| OcypusError::HidApi(e.to_string()))?;
+        Ok(Self { api })
+    }
+}
+
+impl HidTransport for HidApiTransport {
+    fn enumerate(&mut self) -> Result<Vec<DeviceInfo>> {
+        self.api
+            .refresh_devices()
+            .map_err(|e| OcypusError::HidApi(e.to_string()))?;
+        Ok(self.api.device_list().map(DeviceInfo::from).collect())
+    }
+
+    fn open(&mut self, path: &str) -> Result<Box<dyn HidConnection>> {
+        let path = CString::new(path).map_err(|e| OcypusError::Device(e.to_string()))?;
+        let device = self
+            .api
+            .open_path(&path)
+            .map_err(|e| OcypusError::HidApi(e.to_string()))?;
+        Ok(Box::new(HidApiConnection { device }))
+    }
+}
+
+/// A device opened through hidapi
+struct HidApiConnection {
+    device: HidDevice,
+}
+
+impl HidConnection for HidApiConnection {
+    fn write(&mut self, data: &[u8]) -> Result<usize> {
+        self.device
+            .write(data)
+            .map_err(|e| OcypusError::HidApi(e.to_string()))
+    }
+
+    fn read_timeout(&mut self, buf: &mut [u8], timeout_ms: i32) -> Result<usize> {
+        self.device
+            .read_timeout(buf, timeout_ms)
+            .map_err(|e| OcypusError::HidApi(e.to_string()))
+    }
+
+    fn get_feature_report(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.device
+            .get_feature_report(buf)
+            .map_err(|e| OcypusError::HidApi(e.to_string()))
+    }
+
+    fn get_input_report(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.device
+            .get_input_report(buf)
+            .map_err(|e| OcypusError::HidApi(e.to_string()))
+    }
+
+    fn get_report_descriptor(&mut self, buf: &mut [u8]) -> Result<usize> {
+        self.device
+            .get_report_descriptor(buf)
+            .map_err(|e| OcypusError::HidApi(e.to_string()))
+    }
+}
@@ -0,0 +1,279 @@
+use crate::config::{PID, VID};
+use crate::device::DeviceInfo;
+use crate::error::{OcypusError, Result};
+use crate::transport::{HidConnection, HidTransport};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// In-memory transport that records every report written to it.
+///
+/// Clones share state, so a test can hand one clone to `DeviceManager` and
+/// keep another to inspect reports, inject failures or unplug devices.
+#[derive(Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Default)]
+struct MockState {
+    devices: Vec<DeviceInfo>,
+    reports: Vec<(String, Vec<u8>)>,
+    input: VecDeque<Vec<u8>>,
+    feature_reports: HashMap<u8, Vec<u8>>,
+    input_reports: HashMap<u8, Vec<u8>>,
+    descriptor: Option<Vec<u8>>,
+    failing_writes: usize,
+    opens: usize,
+    /// Times each path has been unplugged; connections opened before an
+    /// unplug stay dead even if the device comes back
+    unplugs: HashMap<String, usize>,
+}
+
+impl MockTransport {
+    /// Create a transport with no devices attached
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Describe an Ocypus device at `path`
+    pub fn ocypus_device(path: &str, serial: Option<&str>) -> DeviceInfo {
+        DeviceInfo {
+            path: path.to_string(),
+            vendor_id: VID,
+            product_id: PID,
+            serial: serial.map(str::to_string),
+            manufacturer: Some("Ocypus".to_string()),
+            product: Some("Iota L24".to_string()),
+            interface_number: 0,
+            release_number: 0x0100,
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Attach a device
+    pub fn plug(&self, device: DeviceInfo) {
+        self.state().devices.push(device);
+    }
+
+    /// Detach the device at `path`; writes to open handles start failing
+    pub fn unplug(&self, path: &str) {
+        let mut state = self.state();
+        state.devices.retain(|d| d.path != path);
+        *state.unplugs.entry(path.to_string()).or_default() += 1;
+    }
+
+    /// Make the next `count` writes fail
+    pub fn fail_writes(&self, count: usize) {
+        self.state().failing_writes = count;
+    }
+
+    /// Queue an input report for the next read
+    pub fn push_input(&self, report: Vec<u8>) {
+        self.state().input.push_back(report);
+    }
+
+    /// Answer feature report requests for `report[0]` with `report`
+    pub fn set_feature_report(&self, report: Vec<u8>) {
+        self.state().feature_reports.insert(report[0], report);
+    }
+
+    /// Answer input report requests for `report[0]` with `report`
+    pub fn set_input_report(&self, report: Vec<u8>) {
+        self.state().input_reports.insert(report[0], report);
+    }
+
+    /// Report descriptor returned to readers
+    pub fn set_report_descriptor(&self, descriptor: Vec<u8>) {
+        self.state().descriptor = Some(descriptor);
+    }
+
+    /// Every report successfully written, in order
+    pub fn reports(&self) -> Vec<Vec<u8>> {
+        self.state()
+            .reports
+            .iter()
+            .map(|(_, data)| data.clone())
+            .collect()
+    }
+
+    /// Reports successfully written to the device at `path`
+    pub fn reports_for(&self, path: &str) -> Vec<Vec<u8>> {
+        self.state()
+            .reports
+            .iter()
+            .filter(|(p, _)| p == path)
+            .map(|(_, data)| data.clone())
+            .collect()
+    }
+
+    /// Number of successful `open` calls
+    pub fn open_count(&self) -> usize {
+        self.state().opens
+    }
+}
+
+impl HidTransport for MockTransport {
+    fn enumerate(&mut self) -> Result<Vec<DeviceInfo>> {
+        Ok(self.state().devices.clone())
+    }
+
+    fn open(&mut self, path: &str) -> Result<Box<dyn HidConnection>> {
+        let mut state = self.state();
+        if !state.devices.iter().any(|d| d.path == path) {
+            return Err(OcypusError::HidApi(format!("{}: no such device", path)));
+        }
+
+        state.opens += 1;
+        let unplugs = state.unplugs.get(path).copied().unwrap_or(0);
+        Ok(Box::new(MockConnection {
+            transport: self.clone(),
+            path: path.to_string(),
+            unplugs,
+        }))
+    }
+}
+
+/// A handle opened on a `MockTransport`
+struct MockConnection {
+    transport: MockTransport,
+    path: String,
+    unplugs: usize,
+}
+
+impl MockConnection {
+    fn check_attached(&self, state: &MockState) -> Result<()> {
+        if state.unplugs.get(&self.path).copied().unwrap_or(0) != self.unplugs {
+            return Err(OcypusError::HidApi(format!(
+                "{}: device disconnected",
+                self.path
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl HidConnection for MockConnection {
+    fn write(&mut self, data: &[u8]) -> Result<usize> {
+        let mut state = self.transport.state();
+        self.check_attached(&state)?;
+
+        if state.failing_writes > 0 {
+            state.failing_writes -= 1;
+            return Err(OcypusError::HidApi(format!("{}: write failed", self.path)));
+        }
+
+        state.reports.push((self.path.clone(), data.to_vec()));
+        Ok(data.len())
+    }
+
+    fn read_timeout(&mut self, buf: &mut [u8], _timeout_ms: i32) -> Result<usize> {
+        let mut state = self.transport.state();
+        self.check_attached(&state)?;
+
+        Ok(state
+            .input
+            .pop_front()
+            .map_or(0, |report| copy_into(buf, &report)))
+    }
+
+    fn get_feature_report(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let state = self.transport.state();
+        self.check_attached(&state)?;
+        let report = state.feature_reports.get(&buf[0]).ok_or_else(|| {
+            OcypusError::HidApi(format!("{}: no feature report {:#04x}", self.path, buf[0]))
+        })?;
+        Ok(copy_into(buf, report))
+    }
+
+    fn get_input_report(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let state = self.transport.state();
+        self.check_attached(&state)?;
+        let report = state.input_reports.get(&buf[0]).ok_or_else(|| {
+            OcypusError::HidApi(format!("{}: no input report {:#04x}", self.path, buf[0]))
+        })?;
+        Ok(copy_into(buf, report))
+    }
+
+    fn get_report_descriptor(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let state = self.transport.state();
+        self.check_attached(&state)?;
+        let descriptor = state
+            .descriptor
+            .as_ref()
+            .ok_or_else(|| OcypusError::HidApi(format!("{}: no report descriptor", self.path)))?;
+        Ok(copy_into(buf, descriptor))
+    }
+}
+
+/// Copy as much of `data` as fits into `buf`, returning the length copied
+fn copy_into(buf: &mut [u8], data: &[u8]) -> usize {
+    let len = data.len().min(buf.len());
+    buf[..len].copy_from_slice(&data[..len]);
+    len
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_records_reports() {
+        let mock = MockTransport::new();
+        mock.plug(MockTransport::ocypus_device("/dev/hidraw3", Some("A")));
+
+        let mut transport = mock.clone();
+        let mut conn = transport.open("/dev/hidraw3").unwrap();
+        assert_eq!(conn.write(&[7, 1, 2]).unwrap(), 3);
+
+        mock.fail_writes(1);
+        assert!(conn.write(&[7, 3, 4]).is_err());
+        conn.write(&[7, 5, 6]).unwrap();
+
+        assert_eq!(mock.reports(), vec![vec![7, 1, 2], vec![7, 5, 6]]);
+        assert_eq!(mock.reports_for("/dev/hidraw9"), Vec::<Vec<u8>>::new());
+    }
+
+    #[test]
+    fn test_mock_unplug_kills_open_handles() {
+        let mock = MockTransport::new();
+        let device = MockTransport::ocypus_device("/dev/hidraw3", None);
+        mock.plug(device.clone());
+
+        let mut transport = mock.clone();
+        let mut old = transport.open("/dev/hidraw3").unwrap();
+        mock.unplug("/dev/hidraw3");
+        assert!(transport.enumerate().unwrap().is_empty());
+        assert!(transport.open("/dev/hidraw3").is_err());
+
+        // Replugging doesn't revive the stale handle
+        mock.plug(device);
+        assert!(old.write(&[7]).is_err());
+        let mut new = transport.open("/dev/hidraw3").unwrap();
+        new.write(&[7]).unwrap();
+        assert_eq!(mock.open_count(), 2);
+    }
+
+    #[test]
+    fn test_mock_input_reports() {
+        let mock = MockTransport::new();
+        mock.plug(MockTransport::ocypus_device("/dev/hidraw3", None));
+        mock.push_input(vec![1, 2, 3]);
+
+        let mut conn = mock.clone().open("/dev/hidraw3").unwrap();
+        let mut buf = [0u8; 64];
+        assert_eq!(conn.read_timeout(&mut buf, 100).unwrap(), 3);
+        assert_eq!(&buf[..3], &[1, 2, 3]);
+        assert_eq!(conn.read_timeout(&mut buf, 100).unwrap(), 0);
+
+        mock.set_feature_report(vec![7, 1, 0, 4]);
+        buf[0] = 7;
+        assert_eq!(conn.get_feature_report(&mut buf).unwrap(), 4);
+        assert_eq!(&buf[..4], &[7, 1, 0, 4]);
+        buf[0] = 2;
+        assert!(conn.get_feature_report(&mut buf).is_err());
+        assert!(conn.get_report_descriptor(&mut buf).is_err());
+    }
+}
@@ -0,0 +1,44 @@
+//! HID transports used to talk to the display.
+//!
+//! `DeviceManager` goes through [`HidTransport`] rather than hidapi
+//! directly, so everything above the USB layer can be exercised with the
+//! in-memory [`MockTransport`].
+
+pub mod hid;
+pub mod mock;
+
+use crate::device::DeviceInfo;
+use crate::error::Result;
+
+pub use hid::HidApiTransport;
+pub use mock::MockTransport;
+
+/// Enumerates and opens HID devices
+pub trait HidTransport: Send {
+    /// List every HID device currently attached
+    fn enumerate(&mut self) -> Result<Vec<DeviceInfo>>;
+
+    /// Open the device at `path`
+    fn open(&mut self, path: &str) -> Result<Box<dyn HidConnection>>;
+}
+
+/// An open HID device
+pub trait HidConnection: Send {
+    /// Write an output report, returning the number of bytes written
+    fn write(&mut self, data: &[u8]) -> Result<usize>;
+
+    /// Read an input report into `buf`, waiting at most `timeout_ms`
+    /// milliseconds (-1 blocks). Returns 0 if nothing arrived in time.
+    fn read_timeout(&mut self, buf: &mut [u8], timeout_ms: i32) -> Result<usize>;
+
+    /// Request a feature report; `buf[0]` holds the report ID on entry.
+    /// Returns the number of bytes read, including the report ID.
+    fn get_feature_report(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+    /// Request an input report over the control endpoint; `buf[0]` holds
+    /// the report ID on entry
+    fn get_input_report(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+    /// Read the raw HID report descriptor
+    fn get_report_descriptor(&mut self, buf: &mut [u8]) -> Result<usize>;
+}